@@ -0,0 +1,24 @@
+from os import PathLike
+from typing import Callable
+
+class Q3Error(Exception): ...
+
+class Store:
+    def expand(self, keep_going: bool = False) -> dict[str, str]: ...
+    def keys(self) -> list[str]: ...
+    def kind(self, id: str) -> str: ...
+    def dependencies(self, id: str) -> list[str]: ...
+    def __getitem__(self, id: str) -> str: ...
+    def __contains__(self, id: str) -> bool: ...
+    def __len__(self) -> int: ...
+
+def load(path: str | PathLike[str]) -> Store: ...
+def loads(source: str) -> Store: ...
+def register_transform(name: str, function: Callable[[list[str]], list[str] | str]) -> None: ...
+
+def quote(input: list[str]) -> list[str]: ...
+def trim(input: list[str]) -> list[str]: ...
+def normalize_spaces(input: list[str]) -> list[str]: ...
+def uniq(input: list[str]) -> list[str]: ...
+def join_or(input: list[str]) -> str: ...
+def join_and(input: list[str]) -> str: ...
@@ -0,0 +1,314 @@
+//! Python bindings of q³, built with [maturin](https://www.maturin.rs):
+//!
+//! ```python
+//! import q3
+//!
+//! q3.register_transform("lower", lambda items: [item.lower() for item in items])
+//!
+//! store = q3.load("query.toml")
+//! store.expand()
+//! print(store["query"])
+//! ```
+//!
+//! The module also provides the helpers scripts import with `from q3 import *`, so registered
+//! transforms can be called from the scripts of lists and generators.
+//!
+//! Scripts with a `timeout` or a `memory_limit` run in a new interpreter started from
+//! `sys.executable`, which only knows the built-in helpers: transforms registered with
+//! `register_transform` aren't available to them.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyKeyError, PyTypeError};
+use pyo3::prelude::*;
+
+use q3::worker::WorkerCommand;
+use q3::{Config, Identify, QStore};
+
+create_exception!(
+    q3,
+    Q3Error,
+    PyException,
+    "Raised when a q3 file can't be loaded or expanded"
+);
+
+fn to_py_err(err: q3::Q3Error) -> PyErr {
+    Q3Error::new_err(err.to_string())
+}
+
+/// Components of a q3 file
+#[pyclass(module = "q3")]
+struct Store {
+    store: QStore,
+    /// Causes of the failed expansions, by component id
+    failures: HashMap<String, String>,
+}
+
+impl TryFrom<Config> for Store {
+    type Error = PyErr;
+
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            store: config.try_into().map_err(to_py_err)?,
+            failures: HashMap::new(),
+        })
+    }
+}
+
+#[pymethods]
+impl Store {
+    /// Expands every component.
+    ///
+    /// Raises `Q3Error` when a component fails to expand, unless `keep_going` is set. The
+    /// failures are returned as a dict of error messages by component id.
+    #[pyo3(signature = (keep_going = false))]
+    fn expand(&mut self, keep_going: bool) -> PyResult<HashMap<String, String>> {
+        let errors = self.store.expand_all();
+
+        // Failed components stay failed, they aren't reported again by later expansions
+        self.failures.extend(
+            errors
+                .iter()
+                .map(|(id, err)| (id.to_string(), err.to_string())),
+        );
+
+        match errors.is_empty() || keep_going {
+            true => Ok(self.failures.clone()),
+            false => Err(to_py_err(q3::Q3Error::ExpansionFailed(errors))),
+        }
+    }
+
+    /// Ids of the components, sorted
+    fn keys(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .store
+            .components
+            .keys()
+            .map(|id| id.to_string())
+            .collect();
+        ids.sort();
+
+        ids
+    }
+
+    /// Kind of a component: `query`, `list` or `generator`
+    fn kind(&self, id: &str) -> PyResult<&'static str> {
+        self.store
+            .get(id)
+            .map(|component| component.kind())
+            .ok_or_else(|| PyKeyError::new_err(id.to_string()))
+    }
+
+    /// Ids of the components a query references
+    fn dependencies(&self, id: &str) -> PyResult<Vec<String>> {
+        self.store
+            .get(id)
+            .map(|component| {
+                component
+                    .dependencies()
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect()
+            })
+            .ok_or_else(|| PyKeyError::new_err(id.to_string()))
+    }
+
+    fn __getitem__(&self, id: &str) -> PyResult<String> {
+        if let Some(err) = self.failures.get(id) {
+            return Err(Q3Error::new_err(format!("{id}: {err}")));
+        }
+
+        self.store
+            .get(id)
+            .map(|component| component.to_string())
+            .ok_or_else(|| PyKeyError::new_err(id.to_string()))
+    }
+
+    fn __contains__(&self, id: &str) -> bool {
+        self.store.get(id).is_some()
+    }
+
+    fn __len__(&self) -> usize {
+        self.store.components.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let mut ids: Vec<&str> = self
+            .store
+            .components
+            .values()
+            .map(|component| component.get_id().0.as_str())
+            .collect();
+        ids.sort();
+
+        format!("Store([{}])", ids.join(", "))
+    }
+}
+
+/// Reads a q3 file
+#[pyfunction]
+fn load(path: PathBuf) -> PyResult<Store> {
+    Config::from_path(path).map_err(to_py_err)?.try_into()
+}
+
+/// Reads the content of a q3 file
+#[pyfunction]
+fn loads(source: &str) -> PyResult<Store> {
+    source.parse::<Config>().map_err(to_py_err)?.try_into()
+}
+
+/// Makes `function` available to scripts under `name`, next to the built-in helpers imported
+/// with `from q3 import *`
+#[pyfunction]
+#[pyo3(pass_module)]
+fn register_transform(
+    module: &Bound<'_, PyModule>,
+    name: &str,
+    function: Bound<'_, PyAny>,
+) -> PyResult<()> {
+    if !function.is_callable() {
+        return Err(PyTypeError::new_err(format!("{name} is not callable")));
+    }
+
+    // `add` also lists the transform in `__all__`, which `from q3 import *` relies on
+    module.add(name, function)
+}
+
+/// Runs the script a q3 process sends on the standard input, for scripts with limits
+#[pyfunction]
+#[pyo3(name = "_serve")]
+fn serve() -> PyResult<()> {
+    q3::worker::serve().map_err(to_py_err)
+}
+
+/// Runs the scripts with limits in a new interpreter, importing this module from the same paths
+fn register_worker(py: Python<'_>) -> PyResult<()> {
+    let sys = py.import_bound("sys")?;
+    let executable: Option<PathBuf> = sys.getattr("executable")?.extract()?;
+
+    // Embedding applications may not have an interpreter to start, scripts with limits then fail
+    let Some(program) = executable.filter(|program| !program.as_os_str().is_empty()) else {
+        return Ok(());
+    };
+
+    q3::worker::register(WorkerCommand {
+        program,
+        args: vec![
+            "-c".into(),
+            format!(
+                "import sys; sys.path[:0] = {}; import q3; q3._serve()",
+                sys.getattr("path")?.repr()?
+            ),
+        ],
+    });
+
+    Ok(())
+}
+
+#[pymodule]
+#[pyo3(name = "q3")]
+fn q3_python(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    q3::script::add_helpers(module)?;
+    register_worker(module.py())?;
+
+    module.add_class::<Store>()?;
+    module.add_function(wrap_pyfunction!(load, module)?)?;
+    module.add_function(wrap_pyfunction!(loads, module)?)?;
+    module.add_function(wrap_pyfunction!(register_transform, module)?)?;
+    module.add_function(wrap_pyfunction!(serve, module)?)?;
+    module.add("Q3Error", module.py().get_type_bound::<Q3Error>())?;
+
+    Ok(())
+}
+
+#[test]
+fn test_module() {
+    pyo3::prepare_freethreaded_python();
+
+    Python::with_gil(|py| {
+        let module = PyModule::new_bound(py, "q3").unwrap();
+        q3_python(&module).unwrap();
+
+        // Scripts import their helpers and the registered transforms from `q3`
+        py.import_bound("sys")
+            .unwrap()
+            .getattr("modules")
+            .unwrap()
+            .set_item("q3", &module)
+            .unwrap();
+
+        let err = to_py_err(q3::Q3Error::IdNotFound("missing".into()));
+        assert!(err.is_instance_of::<Q3Error>(py));
+        assert_eq!(
+            err.value_bound(py).to_string(),
+            "Id not found. Id missing cannot be found in the store"
+        );
+
+        let test = r##"
+import q3
+
+q3.register_transform("shout", lambda items: [item.upper() for item in items])
+
+try:
+    q3.register_transform("broken", "not a function")
+    raise AssertionError("registered a string")
+except TypeError:
+    pass
+
+store = q3.loads("""
+[list.words]
+value = "lorem ipsum"
+separator = " "
+script = '''
+from q3 import *
+value = join_or(shout(value))
+'''
+
+[query.title]
+value = "title:(#{words})"
+
+[query.broken]
+value = "#{title} #{missing}"
+""")
+
+assert store.kind("words") == "list"
+assert store.kind("title") == "query"
+assert store.dependencies("broken") == ["title", "missing"]
+
+try:
+    store.expand()
+    raise AssertionError("expanded a query referencing a missing component")
+except q3.Q3Error as err:
+    assert "broken" in str(err)
+
+failures = store.expand(keep_going=True)
+assert list(failures) == ["broken"]
+
+assert store["title"] == "title:(LOREM OR IPSUM)"
+assert "words" in store and len(store) == 3
+
+try:
+    store["broken"]
+    raise AssertionError("got a failed query")
+except q3.Q3Error:
+    pass
+
+for lookup in (store.__getitem__, store.kind, store.dependencies):
+    try:
+        lookup("unknown")
+        raise AssertionError("found an unknown component")
+    except KeyError:
+        pass
+
+try:
+    q3.loads("[query.title]")
+    raise AssertionError("loaded an invalid q3 file")
+except q3.Q3Error:
+    pass
+"##;
+
+        py.run_bound(test, None, None).unwrap();
+    });
+}
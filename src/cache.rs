@@ -0,0 +1,145 @@
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sandbox, Q3Error};
+
+/// Directory the command line tool caches script results in, relative to the working directory
+pub const CACHE_DIR: &str = ".q3-cache";
+
+static CACHE: RwLock<Option<Cache>> = RwLock::new(None);
+
+/// 64 bits FNV-1a hash of `parts`. Stable across platforms and releases, but not
+/// cryptographic: it tells when inputs change, it doesn't protect them from tampering.
+pub(crate) fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    hash
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    key: Vec<String>,
+    value: String,
+}
+
+/// Results of scripts stored on disk, one file per script and input
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Full key of `parts`, which also holds the version of q3 so upgrades don't reuse results of
+    /// older helpers
+    fn key<'a>(parts: &[&'a str]) -> Vec<&'a str> {
+        let mut key: Vec<&str> = vec![env!("CARGO_PKG_VERSION")];
+        key.extend(parts);
+
+        key
+    }
+
+    fn path(&self, key: &[&str]) -> PathBuf {
+        self.dir.join(format!("{:016x}", fnv1a(key)))
+    }
+
+    /// Cached result of `parts`. Entries hold their full key, as the name of their file is a hash
+    /// which may collide.
+    pub fn get(&self, parts: &[&str]) -> Option<String> {
+        let key = Self::key(parts);
+        let entry: Entry =
+            serde_json::from_str(&std::fs::read_to_string(self.path(&key)).ok()?).ok()?;
+
+        (entry.key == key).then_some(entry.value)
+    }
+
+    /// Stores `value` as the result of `parts`. The cache only saves time, failing to write to it
+    /// is not an error.
+    pub fn put(&self, parts: &[&str], value: &str) {
+        let key = Self::key(parts);
+        let path = self.path(&key);
+        let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+
+        let Ok(entry) = serde_json::to_string(&Entry {
+            key: key.iter().map(|part| part.to_string()).collect(),
+            value: value.into(),
+        }) else {
+            return;
+        };
+
+        // Written aside then renamed, so concurrent runs never read a partial entry
+        let _ = std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(&partial, entry))
+            .and_then(|_| std::fs::rename(&partial, &path));
+    }
+}
+
+/// Caches the results of the scripts of lists and generators in `cache` from now on
+pub fn enable(cache: Cache) {
+    *CACHE.write().unwrap_or_else(|err| err.into_inner()) = Some(cache);
+}
+
+/// Result of `run` for `parts`, read from the cache if it is enabled and holds it
+pub(crate) fn cached(
+    parts: &[&str],
+    run: impl FnOnce() -> Result<String, Q3Error>,
+) -> Result<String, Q3Error> {
+    let cache = CACHE.read().unwrap_or_else(|err| err.into_inner()).clone();
+
+    let Some(cache) = cache else {
+        return run();
+    };
+
+    // The sandbox changes what scripts may do, hence their results
+    let sandbox = serde_json::to_string(&sandbox::current()).unwrap_or_default();
+    let mut key: Vec<&str> = vec![&sandbox];
+    key.extend(parts);
+
+    if let Some(value) = cache.get(&key) {
+        return Ok(value);
+    }
+
+    let value = run()?;
+    cache.put(&key, &value);
+
+    Ok(value)
+}
+
+#[test]
+fn test_cache() {
+    let dir = std::env::temp_dir().join(format!("q3-cache-test-{}", std::process::id()));
+    let cache = Cache::new(&dir);
+
+    assert_eq!(cache.get(&["list", "script", "lorem"]), None);
+
+    cache.put(&["list", "script", "lorem"], "\"lorem\"");
+
+    assert_eq!(
+        cache.get(&["list", "script", "lorem"]),
+        Some("\"lorem\"".into())
+    );
+    assert_eq!(cache.get(&["list", "script", "ipsum"]), None);
+    assert_eq!(cache.get(&["list", "script lorem"]), None);
+
+    // An entry found under the hash of another key, as after a collision
+    std::fs::copy(
+        cache.path(&Cache::key(&["list", "script", "lorem"])),
+        cache.path(&Cache::key(&["list", "script", "dolor"])),
+    )
+    .unwrap();
+    assert_eq!(cache.get(&["list", "script", "dolor"]), None);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -1,12 +1,319 @@
-use clap::Parser;
+use std::path::PathBuf;
 
-/// A fictional versioning CLI
+use clap::{Args, Parser, Subcommand};
+
+use q3::endpoint::Engine;
+use q3::output::OutputFormat;
+use q3::report::ReportFormat;
+
+/// Command line interface of q³.
+///
+/// Every command exits with `0` on success, `1` when the q3 file is invalid or can't be expanded,
+/// and `2` when the command line itself is invalid.
 #[derive(Debug, Parser)] // requires `derive` feature
 #[command(name = "q³")]
-#[command(about = "lorem", long_about = Some("q³ helps you to build higher dimension queries"))]
+#[command(
+    about = "Build search queries from lists, generators and nested queries",
+    long_about = Some("q³ helps you to build higher dimension queries"),
+    after_help = "Exit codes: 0 on success, 1 on invalid or failing q3 files, 2 on invalid arguments"
+)]
 pub struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "run every script instead of reusing results cached in .q3-cache"
+    )]
+    pub no_cache: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "run scripts in separate processes, with safe builtins only and no imports but q3 and --allow-module"
+    )]
+    pub sandbox: bool,
+    #[arg(
+        long = "allow-module",
+        global = true,
+        help = "module sandboxed scripts may import, with its submodules, can be repeated",
+        name = "MODULE",
+        requires = "sandbox"
+    )]
+    pub allowed_modules: Vec<String>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Expand every component and print the result
+    Build(BuildArgs),
+    /// Parse and validate the q3 file without running any script
+    Check(ConfigArgs),
+    /// Expand the q3 file and print a single component
+    Get(GetArgs),
+    /// Show step by step how a component is expanded, as a tree
+    Explain(ExplainArgs),
+    /// List components with their kind and dependencies
+    #[command(alias = "list")]
+    Ls(LsArgs),
+    /// Print the dependency graph of the components in the DOT format
+    Graph(ConfigArgs),
+    /// Show the queries added, removed or changed between two versions of a q3 file
+    Diff(DiffArgs),
+    /// Re-expand the q3 file whenever it or its list files change, printing changed queries
+    ///
+    /// Only the q3 file and the files its lists are read from are watched, modules imported by
+    /// scripts are not.
+    Watch(WatchArgs),
+    /// Evaluate expanded queries against a local corpus
+    Run(RunArgs),
+    /// Compute precision and recall of queries against the relevance judgements of the q3 file
+    Eval(RunArgs),
+    /// Send expanded queries to a search endpoint and count their hits
+    Count(CountArgs),
+    /// Report the hits each item of a list brings to a query
+    ExplainHits(ExplainHitsArgs),
+    /// Print a Markdown or HTML report of every query with the lists and generators it uses
+    Report(ReportArgs),
+    /// Browse components, their expansion and dependencies in an interactive terminal UI
+    #[command(alias = "tui")]
+    Browse(ConfigArgs),
+    /// Try templates, transforms and temporary components interactively
+    Repl(ConfigArgs),
+    /// Run a script received on the standard input, for `build --jobs`
+    #[command(hide = true)]
+    Worker,
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigArgs {
+    #[arg(help = "Path to the q3 file", name = "query.q3")]
+    pub nsq: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct LsArgs {
+    #[arg(help = "Path to the q3 file", name = "query.q3")]
+    pub nsq: PathBuf,
+    #[arg(
+        long = "tag",
+        help = "only list components with this tag, can be repeated",
+        name = "TAG"
+    )]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct FormatArgs {
+    #[arg(long, help = "pretty print queries on several lines")]
+    pub pretty: bool,
+    #[arg(
+        long,
+        help = "remove redundant parentheses and duplicate clauses from queries"
+    )]
+    pub normalize: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct BuildArgs {
+    #[arg(help = "Path to the q3 file", name = "query.q3")]
+    pub nsq: PathBuf,
+    #[arg(
+        long,
+        short,
+        help = "print the components that expanded successfully even if others failed"
+    )]
+    pub keep_going: bool,
+    #[arg(
+        long,
+        help = "write each query to its own file in this directory, along with a manifest.json"
+    )]
+    pub out_dir: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        help = "format of the files written to --out-dir",
+        default_value = "txt"
+    )]
+    pub out_format: OutputFormat,
+    #[arg(
+        long,
+        help = "render the queries with this template file instead of printing a table",
+        conflicts_with = "out_dir"
+    )]
+    pub template: Option<PathBuf>,
+    #[arg(
+        long = "tag",
+        help = "only output queries with this tag, can be repeated",
+        name = "TAG"
+    )]
+    pub tags: Vec<String>,
+    #[arg(
+        long,
+        short,
+        help = "expand up to this many independent components at a time, running scripts in separate processes",
+        default_value = "1"
+    )]
+    pub jobs: usize,
+    #[arg(
+        long,
+        help = "write the expanded components and a hash of their inputs to this lock file"
+    )]
+    pub lock: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "fail if the expansion differs from the lock file, q3.lock unless --lock is set"
+    )]
+    pub locked: bool,
+    #[command(flatten)]
+    pub format: FormatArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct GetArgs {
+    #[arg(help = "Path to the q3 file", name = "query.q3")]
+    pub nsq: PathBuf,
+    #[arg(help = "Id of the component", name = "ID")]
+    pub id: String,
+    #[command(flatten)]
+    pub format: FormatArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ExplainArgs {
     #[arg(help = "Path to the q3 file", name = "query.q3")]
-    pub nsq: std::path::PathBuf,
+    pub nsq: PathBuf,
+    #[arg(help = "Id of the component", name = "ID")]
+    pub id: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    #[arg(help = "Path to the q3 file", name = "query.q3")]
+    pub nsq: PathBuf,
+    #[arg(
+        long,
+        short,
+        value_enum,
+        help = "format of the report",
+        default_value = "md"
+    )]
+    pub format: ReportFormat,
+    #[arg(
+        long = "tag",
+        help = "only report queries with this tag, can be repeated",
+        name = "TAG"
+    )]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    #[arg(
+        help = "Previous version: a q3 file, a lock file or `<revision>:<path>` in git, with `path` relative to the current directory",
+        name = "OLD"
+    )]
+    pub old: String,
+    #[arg(
+        help = "New version: a q3 file, a lock file or `<revision>:<path>` in git, with `path` relative to the current directory",
+        name = "NEW"
+    )]
+    pub new: String,
+}
+
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    #[arg(help = "Path to the q3 file", name = "query.q3")]
+    pub nsq: PathBuf,
+    #[arg(
+        long,
+        help = "Interval between two checks for changes, in milliseconds",
+        default_value = "500"
+    )]
+    pub interval: u64,
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    #[arg(help = "Path to the q3 file", name = "query.q3")]
+    pub nsq: PathBuf,
+    #[arg(long, help = "Path to a JSON lines corpus, one document per line")]
+    pub corpus: PathBuf,
+    #[arg(long, help = "Field holding the document id", default_value = "id")]
+    pub id_field: String,
+    #[arg(
+        long,
+        help = "Comma separated fields to search, every field is searched if not set",
+        value_delimiter = ','
+    )]
+    pub fields: Vec<String>,
     #[arg(long, short, help = "get a request by it's id", name = "ID")]
     pub get: Option<String>,
 }
+
+#[derive(Debug, Args)]
+pub struct CountArgs {
+    #[arg(help = "Path to the q3 file", name = "query.q3")]
+    pub nsq: PathBuf,
+    #[arg(
+        long,
+        help = "URL of the search endpoint, e.g. http://localhost:8983/solr/core"
+    )]
+    pub endpoint: String,
+    #[arg(
+        long,
+        value_enum,
+        help = "API of the search endpoint",
+        default_value = "solr"
+    )]
+    pub engine: Engine,
+    #[arg(long, help = "Request timeout in seconds", default_value = "30")]
+    pub timeout: u64,
+    #[arg(
+        long,
+        short,
+        help = "Maximum number of concurrent requests",
+        default_value = "4"
+    )]
+    pub jobs: usize,
+    #[arg(long, short, help = "get a request by it's id", name = "ID")]
+    pub get: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ExplainHitsArgs {
+    #[arg(help = "Path to the q3 file", name = "query.q3")]
+    pub nsq: PathBuf,
+    #[arg(help = "Id of the query to evaluate", name = "QUERY")]
+    pub query: String,
+    #[arg(long, help = "Id of a list referenced by the query")]
+    pub list: String,
+    #[arg(
+        long,
+        help = "Path to a JSON lines corpus, one document per line",
+        required_unless_present = "endpoint",
+        conflicts_with = "endpoint"
+    )]
+    pub corpus: Option<PathBuf>,
+    #[arg(long, help = "Field holding the document id", default_value = "id")]
+    pub id_field: String,
+    #[arg(
+        long,
+        help = "Comma separated fields to search, every field is searched if not set",
+        value_delimiter = ','
+    )]
+    pub fields: Vec<String>,
+    #[arg(
+        long,
+        help = "URL of the search endpoint, e.g. http://localhost:8983/solr/core"
+    )]
+    pub endpoint: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        help = "API of the search endpoint",
+        default_value = "solr"
+    )]
+    pub engine: Engine,
+    #[arg(long, help = "Request timeout in seconds", default_value = "30")]
+    pub timeout: u64,
+}
@@ -0,0 +1,20 @@
+use super::{load_config, CommandResult};
+use crate::cli::ConfigArgs;
+use crate::tui::Browser;
+use q3::QStore;
+
+pub fn browse(args: ConfigArgs) -> CommandResult {
+    let config = load_config(&args.nsq)?;
+    let outputs = config.outputs();
+    let raw: QStore = config.try_into()?;
+    let mut expanded = raw.clone();
+    let errors = expanded.expand_all();
+
+    let browser = Browser::new(&raw, &expanded, errors, std::env::current_dir()?, outputs);
+
+    let mut terminal = ratatui::init();
+    let result = browser.run(&mut terminal);
+    ratatui::restore();
+
+    Ok(result?)
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use super::{format_queries, load_config, print_table, CommandResult};
+use crate::cli::BuildArgs;
+use crate::tui::TableRow;
+use q3::diagnostics::deprecated_references;
+use q3::lock::LockFile;
+use q3::output::write_queries;
+use q3::render::{context, Template};
+use q3::{Q3Error, QStore};
+
+pub fn build(args: BuildArgs) -> CommandResult {
+    let config = load_config(&args.nsq)?;
+    let outputs = config.outputs();
+
+    let template = match &args.template {
+        Some(path) => Some(std::fs::read_to_string(path)?),
+        None => config
+            .output
+            .as_ref()
+            .and_then(|output| output.template.clone()),
+    }
+    .map(|template| Template::parse(&template))
+    .transpose()?;
+
+    let raw: QStore = config.try_into()?;
+
+    for warning in deprecated_references(&raw.components) {
+        eprintln!("warning: {warning}");
+    }
+
+    let mut queries = raw.clone();
+    let errors = queries.expand_all_jobs(args.jobs);
+
+    if !errors.is_empty() && !args.keep_going {
+        return Err(Q3Error::ExpansionFailed(errors).into());
+    }
+
+    let mut queries = queries.without_failed();
+
+    let lock = LockFile::new(&raw, &queries);
+    let lock_path = args
+        .lock
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("q3.lock"));
+
+    if args.locked {
+        let differences = LockFile::from_path(&lock_path)?.differences(&lock);
+
+        if !differences.is_empty() {
+            return Err(Q3Error::LockMismatch(differences).into());
+        }
+    } else if args.lock.is_some() {
+        lock.write(&lock_path)?;
+    }
+
+    queries
+        .components
+        .retain(|_, component| component.metadata().matches_tags(&args.tags));
+
+    format_queries(&mut queries, &args.format)?;
+
+    match (&args.out_dir, template) {
+        (Some(dir), _) => {
+            for (id, path) in write_queries(&queries, &outputs, dir, args.out_format)? {
+                println!("{id} -> {}", dir.join(path).display());
+            }
+        }
+        (None, Some(template)) => print!("{}", template.render(&context(&raw, &queries))?),
+        (None, None) => {
+            let mut table_data = TableRow::from_store(queries);
+            table_data.sort_by(|a, b| a.id.cmp(&b.id));
+
+            print_table(table_data);
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(Q3Error::ExpansionFailed(errors).into()),
+    }
+}
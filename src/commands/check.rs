@@ -0,0 +1,33 @@
+use super::{load_config, CommandResult};
+use crate::cli::ConfigArgs;
+use q3::diagnostics::analyze;
+use q3::Q3Error;
+
+/// Reports every problem of the q3 file at once, without running any script
+pub fn check(args: ConfigArgs) -> CommandResult {
+    let diagnostics = analyze(load_config(&args.nsq)?.components());
+
+    for diagnostic in &diagnostics {
+        match diagnostic.is_error() {
+            true => eprintln!("error: {diagnostic}"),
+            false => eprintln!("warning: {diagnostic}"),
+        }
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .count();
+
+    if errors > 0 {
+        return Err(Q3Error::CheckFailed(errors).into());
+    }
+
+    println!(
+        "{}: ok ({} warning(s))",
+        args.nsq.display(),
+        diagnostics.len()
+    );
+
+    Ok(())
+}
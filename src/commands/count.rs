@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use tabled::settings::Style;
+use tabled::Table;
+
+use super::{load, CommandResult};
+use crate::cli::CountArgs;
+use crate::tui::CountRow;
+use q3::endpoint::Endpoint;
+use q3::{Id, Identify, Q3Components};
+
+pub fn count(args: CountArgs) -> CommandResult {
+    let queries = load(&args.nsq)?;
+    let endpoint = Endpoint::new(
+        args.endpoint,
+        args.engine,
+        Duration::from_secs(args.timeout),
+    );
+
+    let mut expanded_queries: Vec<(Id, String)> = queries
+        .components
+        .values()
+        .filter_map(|component| match component {
+            Q3Components::Query(query) => Some((query.get_id().clone(), query.to_string())),
+            _ => None,
+        })
+        .filter(|(id, _)| args.get.as_ref().is_none_or(|get| *get == id.0))
+        .collect();
+
+    expanded_queries.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+
+    let counts = endpoint.count_all(expanded_queries, args.jobs);
+    let table_data: Vec<CountRow> = counts
+        .iter()
+        .map(|(id, count)| CountRow::new(id, count))
+        .collect();
+
+    println!("{}", Table::new(table_data).with(Style::rounded()));
+
+    match counts.into_iter().find_map(|(_, count)| count.err()) {
+        Some(err) => Err(err.into()),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::CommandResult;
+use crate::cli::DiffArgs;
+use q3::diff::{diff_queries, version, ListChange, QueryChange, Version};
+use q3::lock::LockFile;
+use q3::{Config, Q3Error, QStore};
+
+/// Where a version of a q3 file is read from
+#[derive(Debug, PartialEq)]
+enum Source {
+    File(PathBuf),
+    /// `<revision>:<path>`, the file at `path` as of a git revision, `path` being relative to the
+    /// current directory
+    Git {
+        revision: String,
+        path: PathBuf,
+    },
+}
+
+impl Source {
+    /// Files that exist take precedence over git revisions, so paths holding a `:` stay usable
+    fn parse(source: &str) -> Self {
+        match source.split_once(':') {
+            Some((revision, path))
+                if !revision.is_empty() && !path.is_empty() && !Path::new(source).exists() =>
+            {
+                Self::Git {
+                    revision: revision.into(),
+                    path: path.into(),
+                }
+            }
+            _ => Self::File(source.into()),
+        }
+    }
+
+    fn read(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let (revision, path) = match self {
+            Self::File(path) => return Ok(std::fs::read_to_string(path)?),
+            Self::Git { revision, path } => (revision, path),
+        };
+
+        let name = path
+            .file_name()
+            .ok_or_else(|| format!("{}: not a file", path.display()))?;
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        // `./` makes git resolve the path from `dir` instead of the root of the repository
+        let output = Command::new("git")
+            .current_dir(dir)
+            .arg("show")
+            .arg(format!("{revision}:./{}", name.to_string_lossy()))
+            .output()?;
+
+        match output.status.success() {
+            true => Ok(String::from_utf8(output.stdout)?),
+            false => Err(String::from_utf8_lossy(&output.stderr).trim().into()),
+        }
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Git { revision, path } => write!(f, "{revision}:{}", path.display()),
+        }
+    }
+}
+
+/// Expands a version of a q3 file, or reads it from a lock file. Fails if any component fails to
+/// expand, a partial version would report its failed queries as removed.
+fn load_version(source: &Source) -> Result<Version, Box<dyn std::error::Error>> {
+    let content = source.read()?;
+
+    if let Ok(lock) = content.parse::<LockFile>() {
+        return Ok(Version::from(&lock));
+    }
+
+    let raw: QStore = content.parse::<Config>()?.try_into()?;
+    let mut expanded = raw.clone();
+
+    let errors = expanded.expand_all();
+
+    if !errors.is_empty() {
+        return Err(format!("{source}: {}", Q3Error::ExpansionFailed(errors)).into());
+    }
+
+    Ok(version(&raw, &expanded))
+}
+
+/// Prints the queries added, removed or changed between two versions of a q3 file
+pub fn diff(args: DiffArgs) -> CommandResult {
+    let old = load_version(&Source::parse(&args.old))?;
+    let new = load_version(&Source::parse(&args.new))?;
+
+    for change in diff_queries(&old, &new) {
+        match change {
+            QueryChange::Added { id, query } => println!("+ {id}: {query}"),
+            QueryChange::Removed { id } => println!("- {id}"),
+            QueryChange::Changed { id, diff, lists } => {
+                println!("~ {id}: {diff}");
+
+                for list in lists {
+                    match list {
+                        ListChange::Items { id, added, removed } => {
+                            let items: Vec<String> = added
+                                .iter()
+                                .map(|item| format!("+{item}"))
+                                .chain(removed.iter().map(|item| format!("-{item}")))
+                                .collect();
+
+                            println!("    {id}: {}", items.join(" "));
+                        }
+                        ListChange::Value { id, diff } => println!("    {id}: {diff}"),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_sources() {
+    assert_eq!(
+        Source::parse("HEAD~1:queries/query.toml"),
+        Source::Git {
+            revision: "HEAD~1".into(),
+            path: "queries/query.toml".into()
+        }
+    );
+    assert_eq!(
+        Source::parse("query.toml"),
+        Source::File("query.toml".into())
+    );
+    assert_eq!(
+        Source::parse(":query.toml"),
+        Source::File(":query.toml".into())
+    );
+
+    let dir = std::env::temp_dir().join(format!("q3-diff-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("queries")).unwrap();
+
+    let path = dir.join("queries/query.toml");
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .current_dir(&dir)
+            .args(["-c", "user.name=q3", "-c", "user.email=q3@localhost"])
+            .args(["-c", "commit.gpgsign=false"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?}");
+    };
+
+    std::fs::write(&path, "[query.title]\nvalue = \"title:lorem\"\n").unwrap();
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "lorem"]);
+
+    std::fs::write(
+        &path,
+        "[query.title]\nvalue = \"title:ipsum\"\n\n[query.broken]\nvalue = \"#{missing}\"\n",
+    )
+    .unwrap();
+
+    let committed = load_version(&Source::Git {
+        revision: "HEAD".into(),
+        path: path.clone(),
+    })
+    .unwrap();
+    assert_eq!(committed["title"].value, "title:lorem");
+
+    assert!(load_version(&Source::Git {
+        revision: "HEAD".into(),
+        path: dir.join("queries/missing.toml"),
+    })
+    .is_err());
+
+    let err = load_version(&Source::File(path.clone())).unwrap_err();
+    assert!(err.to_string().contains("broken"));
+
+    let mut raw = QStore::new();
+    raw.insert(q3::Q3Components::Query(
+        q3::Query::new("title", "title:dolor").unwrap(),
+    ));
+    let mut expanded = raw.clone();
+    expanded.expand_all();
+
+    let lock_path = dir.join("q3.lock");
+    LockFile::new(&raw, &expanded).write(&lock_path).unwrap();
+
+    let locked = load_version(&Source::File(lock_path)).unwrap();
+    assert_eq!(locked["title"].value, "title:dolor");
+    assert_eq!(locked["title"].items, None);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,46 @@
+use tabled::settings::Style;
+use tabled::Table;
+
+use super::{expand, load_config, CommandResult};
+use crate::cli::RunArgs;
+use crate::tui::EvaluationRow;
+use q3::corpus::{Corpus, Evaluation};
+use q3::parser::parse_boolean_query;
+use q3::{Identify, Q3Components, Q3Error};
+
+pub fn eval(args: RunArgs) -> CommandResult {
+    let mut config = load_config(&args.nsq)?;
+    let evaluations = config.evaluations.take().unwrap_or_default();
+    let queries = expand(config)?;
+    let corpus = Corpus::from_path(&args.corpus, &args.id_field, args.fields)?;
+
+    let mut table_data: Vec<EvaluationRow> = Vec::new();
+
+    for (id, evaluation) in evaluations {
+        if args.get.as_ref().is_some_and(|get| *get != id) {
+            continue;
+        }
+
+        let query = match queries.get(&id) {
+            Some(Q3Components::Query(query)) => query,
+            _ => return Err(Q3Error::IdNotFound(id).into()),
+        };
+
+        let ast = parse_boolean_query(&query.to_string())?;
+        let matched: Vec<&str> = corpus
+            .search(&ast)
+            .into_iter()
+            .map(|document| document.id.as_str())
+            .collect();
+
+        table_data.push(EvaluationRow::new(
+            query.get_id(),
+            Evaluation::new(&matched, &evaluation.relevant),
+        ));
+    }
+
+    table_data.sort_by(|a, b| a.id.cmp(&b.id));
+    println!("{}", Table::new(table_data).with(Style::rounded()));
+
+    Ok(())
+}
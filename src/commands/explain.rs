@@ -0,0 +1,13 @@
+use super::{load_config, CommandResult};
+use crate::cli::ExplainArgs;
+use q3::trace::trace;
+use q3::{Id, QStore};
+
+/// Prints the expansion trace of a component
+pub fn explain(args: ExplainArgs) -> CommandResult {
+    let queries: QStore = load_config(&args.nsq)?.try_into()?;
+
+    println!("{}", trace(&queries, &Id(args.id))?);
+
+    Ok(())
+}
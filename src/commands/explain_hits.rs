@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use tabled::settings::Style;
+use tabled::Table;
+
+use super::{load_config, CommandResult};
+use crate::cli::ExplainHitsArgs;
+use crate::tui::ContributionRow;
+use q3::contribution::list_variants;
+use q3::corpus::Corpus;
+use q3::endpoint::Endpoint;
+use q3::parser::parse_boolean_query;
+use q3::{Id, QStore};
+
+pub fn explain_hits(args: ExplainHitsArgs) -> CommandResult {
+    let queries: QStore = load_config(&args.nsq)?.try_into()?;
+    let variants = list_variants(&queries, &Id(args.query), &Id(args.list))?;
+
+    let corpus = args
+        .corpus
+        .map(|path| Corpus::from_path(path, &args.id_field, args.fields))
+        .transpose()?;
+
+    let endpoint = args
+        .endpoint
+        .map(|url| Endpoint::new(url, args.engine, Duration::from_secs(args.timeout)));
+
+    let count = |query: &str| -> Result<u64, Box<dyn std::error::Error>> {
+        match (&corpus, &endpoint) {
+            (Some(corpus), _) => Ok(corpus.search(&parse_boolean_query(query)?).len() as u64),
+            (None, Some(endpoint)) => Ok(endpoint.count(query)?),
+            (None, None) => Ok(0),
+        }
+    };
+
+    let hits = count(&variants.full)?;
+    let mut table_data: Vec<ContributionRow> = Vec::new();
+
+    for variant in variants.items {
+        let without = match variant.without {
+            Some(query) => count(&query)?,
+            None => 0,
+        };
+
+        table_data.push(ContributionRow {
+            hits: count(&variant.only)?,
+            unique: hits.saturating_sub(without),
+            item: variant.item,
+        });
+    }
+
+    println!("{hits} hits with the whole list");
+    println!("{}", Table::new(table_data).with(Style::rounded()));
+
+    Ok(())
+}
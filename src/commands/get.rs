@@ -0,0 +1,18 @@
+use super::{format_queries, load, CommandResult};
+use crate::cli::GetArgs;
+use q3::Q3Error;
+
+/// Prints the raw value of a component, so it can be piped to other commands
+pub fn get(args: GetArgs) -> CommandResult {
+    let mut queries = load(&args.nsq)?;
+
+    format_queries(&mut queries, &args.format)?;
+
+    match queries.get(&args.id) {
+        Some(component) => {
+            println!("{}", component);
+            Ok(())
+        }
+        None => Err(Q3Error::IdNotFound(args.id).into()),
+    }
+}
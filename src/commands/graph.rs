@@ -0,0 +1,33 @@
+use super::{load_config, CommandResult};
+use crate::cli::ConfigArgs;
+use q3::{Identify, Q3Components, QStore};
+
+/// Prints the components and their references as a DOT graph
+pub fn graph(args: ConfigArgs) -> CommandResult {
+    let queries: QStore = load_config(&args.nsq)?.try_into()?;
+
+    let mut components: Vec<&Q3Components> = queries.components.values().collect();
+    components.sort_by(|a, b| a.get_id().0.cmp(&b.get_id().0));
+
+    println!("digraph q3 {{");
+
+    for component in &components {
+        let shape = match component {
+            Q3Components::Query(_) => "box",
+            Q3Components::List(_) => "ellipse",
+            Q3Components::Generator(_) => "diamond",
+        };
+
+        println!("    {:?} [shape={}];", component.get_id().0, shape);
+    }
+
+    for component in &components {
+        for dependency in component.dependencies() {
+            println!("    {:?} -> {:?};", component.get_id().0, dependency.0);
+        }
+    }
+
+    println!("}}");
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use tabled::settings::Style;
+use tabled::Table;
+
+use super::{load_config, CommandResult};
+use crate::cli::LsArgs;
+use crate::tui::ComponentRow;
+use q3::QStore;
+
+pub fn ls(args: LsArgs) -> CommandResult {
+    let queries: QStore = load_config(&args.nsq)?.try_into()?;
+
+    let mut table_data: Vec<ComponentRow> = queries
+        .components
+        .values()
+        .filter(|component| component.metadata().matches_tags(&args.tags))
+        .map(ComponentRow::from)
+        .collect();
+    table_data.sort_by(|a, b| a.id.cmp(&b.id));
+
+    println!("{}", Table::new(table_data).with(Style::rounded()));
+
+    Ok(())
+}
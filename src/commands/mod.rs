@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use tabled::settings::object::Rows;
+use tabled::settings::Style;
+use tabled::settings::{measurement::Percent, Width};
+use tabled::settings::{
+    peaker::{PriorityMax, PriorityMin},
+    Padding, Settings,
+};
+use tabled::Table;
+
+use crate::cli::FormatArgs;
+use crate::tui::TableRow;
+use q3::parser::{parse_boolean_query, PrettyPrinter};
+use q3::Config;
+use q3::{Identify, Q3Components, QStore, Query};
+
+mod browse;
+pub use browse::browse;
+
+mod build;
+pub use build::build;
+
+mod check;
+pub use check::check;
+
+mod count;
+pub use count::count;
+
+mod diff;
+pub use diff::diff;
+
+mod eval;
+pub use eval::eval;
+
+mod explain;
+pub use explain::explain;
+
+mod explain_hits;
+pub use explain_hits::explain_hits;
+
+mod get;
+pub use get::get;
+
+mod graph;
+pub use graph::graph;
+
+mod ls;
+pub use ls::ls;
+
+mod repl;
+pub use repl::repl;
+
+mod report;
+pub use report::report;
+
+mod run;
+pub use run::run;
+
+mod watch;
+pub use watch::watch;
+
+pub type CommandResult = Result<(), Box<dyn std::error::Error>>;
+
+/// Reads and expands a q3 file
+pub fn load(path: &Path) -> Result<QStore, Box<dyn std::error::Error>> {
+    expand(load_config(path)?)
+}
+
+pub fn load_config(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+    Ok(Config::from_path(path)?)
+}
+
+pub fn expand(config: Config) -> Result<QStore, Box<dyn std::error::Error>> {
+    let mut queries: QStore = config.try_into()?;
+
+    queries.expand()?;
+
+    Ok(queries)
+}
+
+/// Pretty prints and/or normalizes every expanded query of the store
+pub fn format_queries(queries: &mut QStore, args: &FormatArgs) -> CommandResult {
+    if !args.pretty && !args.normalize {
+        return Ok(());
+    }
+
+    for component in queries.components.values_mut() {
+        if let Q3Components::Query(query) = component {
+            let mut ast = parse_boolean_query(&query.to_string())?;
+
+            if args.normalize {
+                ast = ast.normalize();
+            }
+
+            let formatted = match args.pretty {
+                true => PrettyPrinter::default().print(&ast),
+                false => ast.to_string(),
+            };
+
+            *query = Query::new(query.get_id().to_string(), formatted)?
+                .with_metadata(query.metadata().clone());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn print_table(rows: Vec<TableRow>) {
+    let mut table = Table::new(rows);
+
+    let settings = Settings::new(
+        Width::increase(40).priority::<PriorityMin>(),
+        Width::wrap(Percent(70)).priority::<PriorityMax>(),
+    );
+
+    table
+        .with(settings)
+        .modify(Rows::new(1..), Padding::new(0, 0, 0, 1))
+        .with(Style::rounded());
+
+    println!("{}", table);
+}
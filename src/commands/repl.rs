@@ -0,0 +1,19 @@
+use super::{load_config, CommandResult};
+use crate::cli::ConfigArgs;
+use q3::repl::Repl;
+use q3::QStore;
+
+pub fn repl(args: ConfigArgs) -> CommandResult {
+    let mut queries: QStore = load_config(&args.nsq)?.try_into()?;
+    let errors = queries.expand_all();
+
+    for (id, err) in &errors {
+        eprintln!("warning: {id} failed to expand: {err}");
+    }
+
+    println!("Type :help to list the commands, :quit to leave");
+
+    let mut repl = Repl::new(queries, errors);
+
+    Ok(repl.run(std::io::stdin().lock(), std::io::stdout())?)
+}
@@ -0,0 +1,18 @@
+use super::{load_config, CommandResult};
+use crate::cli::ReportArgs;
+use q3::report::report as render_report;
+use q3::QStore;
+
+/// Prints a document describing every query of the q3 file, expanded as `build` does
+pub fn report(args: ReportArgs) -> CommandResult {
+    let raw: QStore = load_config(&args.nsq)?.try_into()?;
+    let mut queries = raw.clone();
+    let errors = queries.expand_all();
+
+    print!(
+        "{}",
+        render_report(&raw, &queries, &errors, &args.tags, args.format)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use tabled::settings::Style;
+use tabled::Table;
+
+use super::{load, CommandResult};
+use crate::cli::RunArgs;
+use crate::tui::HitsRow;
+use q3::corpus::Corpus;
+use q3::parser::parse_boolean_query;
+use q3::{Identify, Q3Components};
+
+pub fn run(args: RunArgs) -> CommandResult {
+    let queries = load(&args.nsq)?;
+    let corpus = Corpus::from_path(&args.corpus, &args.id_field, args.fields)?;
+
+    let mut table_data: Vec<HitsRow> = Vec::new();
+
+    for component in queries.components.values() {
+        if let Q3Components::Query(query) = component {
+            if args.get.as_ref().is_some_and(|id| *id != query.get_id().0) {
+                continue;
+            }
+
+            let ast = parse_boolean_query(&query.to_string())?;
+            table_data.push(HitsRow::new(query.get_id(), corpus.search(&ast)));
+        }
+    }
+
+    table_data.sort_by(|a, b| a.id.cmp(&b.id));
+    println!("{}", Table::new(table_data).with(Style::rounded()));
+
+    Ok(())
+}
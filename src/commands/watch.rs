@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::{load_config, CommandResult};
+use crate::cli::WatchArgs;
+use q3::diff::word_diff;
+use q3::{Id, Q3Components, Q3Error, QStore};
+
+/// A q3 file loaded in the store, both before and after expansion
+struct Snapshot {
+    raw: QStore,
+    expanded: QStore,
+    files: Vec<PathBuf>,
+}
+
+fn modification_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|file| {
+            std::fs::metadata(file)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
+
+fn read(path: &Path) -> Result<(QStore, Vec<PathBuf>), Box<dyn std::error::Error>> {
+    let config = load_config(path)?;
+    let mut files = vec![path.to_path_buf()];
+    files.extend(config.list_files());
+
+    Ok((config.try_into()?, files))
+}
+
+fn report(errors: Vec<(Id, Q3Error)>) {
+    if !errors.is_empty() {
+        eprintln!("error: {}", Q3Error::ExpansionFailed(errors));
+    }
+}
+
+fn queries(store: &QStore) -> Vec<(Id, String)> {
+    let mut queries: Vec<(Id, String)> = store
+        .components
+        .iter()
+        .filter(|(id, _)| !store.is_failed(id))
+        .filter_map(|(id, component)| match component {
+            Q3Components::Query(query) => Some((id.clone(), query.to_string())),
+            _ => None,
+        })
+        .collect();
+
+    queries.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+    queries
+}
+
+impl Snapshot {
+    fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let (raw, files) = read(path)?;
+        let mut expanded = raw.clone();
+
+        report(expanded.expand_all());
+
+        Ok(Self {
+            raw,
+            expanded,
+            files,
+        })
+    }
+
+    /// Reloads the q3 file, only expanding the components that changed or that reference a
+    /// component that changed
+    fn reload(&self, path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let (raw, files) = read(path)?;
+
+        let changed: HashSet<Id> = raw
+            .components
+            .keys()
+            .chain(self.raw.components.keys())
+            .filter(|id| raw.components.get(*id) != self.raw.components.get(*id))
+            .cloned()
+            .collect();
+
+        let affected = raw.affected_by(&changed);
+        let mut expanded = raw.clone();
+
+        for (id, component) in &self.expanded.components {
+            if !affected.contains(id) && !self.expanded.is_failed(id) {
+                expanded.insert(component.clone());
+            }
+        }
+
+        report(expanded.expand_all());
+
+        Ok(Self {
+            raw,
+            expanded,
+            files,
+        })
+    }
+}
+
+/// Prints the queries of the q3 file, then the changes to them whenever the q3 file or one of its
+/// list files changes. Modules imported by scripts are not watched.
+pub fn watch(args: WatchArgs) -> CommandResult {
+    let mut snapshot = Snapshot::load(&args.nsq)?;
+    let mut stamps = modification_times(&snapshot.files);
+
+    for (id, query) in queries(&snapshot.expanded) {
+        println!("{id}: {query}");
+    }
+
+    loop {
+        std::thread::sleep(Duration::from_millis(args.interval));
+
+        let current_stamps = modification_times(&snapshot.files);
+
+        if current_stamps == stamps {
+            continue;
+        }
+
+        stamps = current_stamps;
+
+        let reloaded = match snapshot.reload(&args.nsq) {
+            Ok(reloaded) => reloaded,
+            Err(err) => {
+                eprintln!("error: {err}");
+                continue;
+            }
+        };
+
+        let previous = queries(&snapshot.expanded);
+        let current = queries(&reloaded.expanded);
+
+        for (id, query) in &current {
+            match previous.iter().find(|(previous_id, _)| previous_id == id) {
+                Some((_, previous_query)) if previous_query != query => {
+                    println!("~ {id}: {}", word_diff(previous_query, query))
+                }
+                Some(_) => (),
+                None => println!("+ {id}: {query}"),
+            }
+        }
+
+        for (id, _) in &previous {
+            if !current.iter().any(|(current_id, _)| current_id == id) {
+                println!("- {id}");
+            }
+        }
+
+        stamps = modification_times(&reloaded.files);
+        snapshot = reloaded;
+    }
+}
+
+#[test]
+fn test_reload() {
+    let dir = std::env::temp_dir().join(format!("q3-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("query.toml");
+    let list_path = dir.join("terms.txt");
+
+    std::fs::write(&list_path, "lorem,ipsum").unwrap();
+    std::fs::write(
+        &path,
+        format!(
+            "[list.terms]\nfile = {:?}\nseparator = \",\"\n\n\
+             [query.title]\nvalue = \"title:(#{{terms}})\"\n\n\
+             [query.recent]\nvalue = \"#{{title}} AND year:2020\"\n\n\
+             [query.other]\nvalue = \"title:dolor\"\n",
+            list_path.display().to_string()
+        ),
+    )
+    .unwrap();
+
+    let mut snapshot = Snapshot::load(&path).unwrap();
+    assert_eq!(snapshot.files, vec![path.clone(), list_path.clone()]);
+
+    // Marks the expansion of `other`, which is kept as long as it isn't expanded again
+    snapshot.expanded.insert(Q3Components::Query(
+        q3::Query::new("other", "title:marker").unwrap(),
+    ));
+    snapshot.expanded.expand_all();
+
+    std::fs::write(&list_path, "lorem,sit").unwrap();
+    let reloaded = snapshot.reload(&path).unwrap();
+
+    std::fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(
+        queries(&reloaded.expanded),
+        vec![
+            (Id("other".into()), "title:marker".into()),
+            (
+                Id("recent".into()),
+                "title:(lorem,sit) AND year:2020".into()
+            ),
+            (Id("title".into()), "title:(lorem,sit)".into()),
+        ]
+    );
+}
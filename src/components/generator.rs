@@ -1,18 +1,20 @@
 use std::fmt::Display;
 
-use super::{Id, Identify};
+use super::{Id, Identify, Limits, Metadata, ScriptLang};
+use crate::cache::cached;
 use crate::store::QStore;
+use crate::worker::run_script;
 use crate::Expand;
 use crate::Q3Error;
 
-use pyo3::prelude::*;
-use pyo3::types::IntoPyDict;
-
 #[derive(Debug, Clone, PartialEq)]
 pub struct Generator {
     pub id: Id,
     pub script: String,
+    pub script_lang: ScriptLang,
     pub value: Option<String>,
+    pub limits: Limits,
+    pub metadata: Metadata,
 }
 
 impl Display for Generator {
@@ -34,19 +36,14 @@ impl Expand for Generator {
     type State = QStore;
 
     fn expand(&mut self, state: QStore) -> Result<Self::State, Q3Error> {
-        self.value = Some(Python::with_gil(|py| {
-            let locals = [("value", None::<String>)].into_py_dict_bound(py);
-            py.run_bound(&self.script, Some(&locals), None)
-                .map_err(|err| Q3Error::PythonScriptFailed(err))?;
-
-            let value: String = locals
-                .get_item("value")
-                .map_err(|err| Q3Error::PythonScriptFailed(err))?
-                .ok_or(Q3Error::PythonScriptVariableNotAssigned)?
-                .extract()
-                .map_err(|err| Q3Error::PythonScriptFailed(err))?;
-
-            Ok::<String, Q3Error>(value)
+        if self.value.is_some() {
+            return Ok(state);
+        }
+
+        let lang = self.script_lang.to_string();
+
+        self.value = Some(cached(&["generator", &lang, &self.script], || {
+            run_script(&self.id, &self.script, self.script_lang, None, &self.limits)
         })?);
 
         Ok(state)
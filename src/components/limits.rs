@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{Id, Q3Error};
+
+/// Limits of the script of a list or generator. A script with limits runs in its own process,
+/// see [`crate::worker`].
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Limits {
+    /// Seconds the script may run for
+    pub timeout: Option<f64>,
+    /// MiB of address space the process running the script may use
+    pub memory_limit: Option<u64>,
+}
+
+impl Limits {
+    pub fn is_empty(&self) -> bool {
+        self.timeout.is_none() && self.memory_limit.is_none()
+    }
+
+    /// Time the script of `id` may run for. Fails if the timeout is negative, not a number or too
+    /// large.
+    pub fn timeout(&self, id: &Id) -> Result<Option<Duration>, Q3Error> {
+        self.timeout
+            .map(|timeout| {
+                Duration::try_from_secs_f64(timeout).map_err(|err| {
+                    Q3Error::InvalidLimits(id.clone(), format!("timeout of {timeout}s: {err}"))
+                })
+            })
+            .transpose()
+    }
+
+    /// Bytes of address space the process running the script of `id` may use. Fails if the
+    /// memory limit doesn't fit in 64 bits once in bytes.
+    pub fn memory_limit_bytes(&self, id: &Id) -> Result<Option<u64>, Q3Error> {
+        self.memory_limit
+            .map(|memory_limit| {
+                memory_limit.checked_mul(1024 * 1024).ok_or_else(|| {
+                    Q3Error::InvalidLimits(
+                        id.clone(),
+                        format!("memory limit of {memory_limit} MiB is too large"),
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// Checks the limits of the script of `id` can be enforced
+    pub fn validate(&self, id: &Id) -> Result<(), Q3Error> {
+        self.timeout(id)?;
+        self.memory_limit_bytes(id)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_validate() {
+    let id = Id("words".into());
+    let limits = |timeout: Option<f64>, memory_limit: Option<u64>| Limits {
+        timeout,
+        memory_limit,
+    };
+
+    assert!(limits(Some(1.5), Some(512)).validate(&id).is_ok());
+    assert!(limits(None, None).validate(&id).is_ok());
+
+    for timeout in [-1.0, f64::NAN, f64::INFINITY, 1e30] {
+        assert!(matches!(
+            limits(Some(timeout), None).validate(&id),
+            Err(Q3Error::InvalidLimits(..))
+        ));
+    }
+
+    assert!(matches!(
+        limits(None, Some(u64::MAX / 1024)).validate(&id),
+        Err(Q3Error::InvalidLimits(..))
+    ));
+}
@@ -1,24 +1,48 @@
 use std::fmt::Display;
 
-use super::{Id, Identify};
+use super::{Id, Identify, Limits, Metadata, ScriptLang};
+use crate::cache::cached;
 use crate::store::QStore;
+use crate::worker::run_script;
 use crate::Expand;
 use crate::Q3Error;
 
-use pyo3::prelude::*;
-use pyo3::types::IntoPyDict;
-
 #[derive(Debug, Clone, PartialEq)]
 pub struct List {
     pub id: Id,
+    /// Raw data of the list
     pub value: String,
     pub separator: String,
     pub script: Option<String>,
+    pub script_lang: ScriptLang,
+    /// Result of the script, set once the list has been expanded
+    pub output: Option<String>,
+    pub limits: Limits,
+    pub metadata: Metadata,
+}
+
+impl List {
+    /// Items of the list, before any script is applied
+    pub fn items(&self) -> Vec<&str> {
+        self.value
+            .split(&self.separator)
+            .filter(|elem| !elem.is_empty())
+            .collect()
+    }
+
+    /// Returns a copy of the list holding only `items`
+    pub fn with_items(&self, items: &[&str]) -> Self {
+        Self {
+            value: items.join(&self.separator),
+            output: None,
+            ..self.clone()
+        }
+    }
 }
 
 impl Display for List {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.value)
+        write!(f, "{}", self.output.as_ref().unwrap_or(&self.value))
     }
 }
 
@@ -32,27 +56,25 @@ impl Expand for List {
     type State = QStore;
 
     fn expand(&mut self, state: QStore) -> Result<Self::State, Q3Error> {
+        if self.output.is_some() {
+            return Ok(state);
+        }
+
         if let Some(script) = &self.script {
-            self.value = Python::with_gil(|py| {
-                let list_data = self
-                    .value
-                    .split(&self.separator)
-                    .filter(|elem| !elem.is_empty())
-                    .collect::<Vec<&str>>();
-
-                let locals = [("value", list_data.clone())].into_py_dict_bound(py);
-                py.run_bound(script, Some(&locals), None)
-                    .map_err(|err| Q3Error::PythonScriptFailed(err))?;
-
-                let value: String = locals
-                    .get_item("value")
-                    .map_err(|err| Q3Error::PythonScriptFailed(err))?
-                    .ok_or(Q3Error::PythonScriptVariableNotAssigned)?
-                    .extract()
-                    .map_err(|err| Q3Error::PythonScriptFailed(err))?;
-
-                Ok::<String, Q3Error>(value)
-            })?;
+            let items = self.items();
+            let lang = self.script_lang.to_string();
+            let mut key: Vec<&str> = vec!["list", &lang, script];
+            key.extend(&items);
+
+            self.output = Some(cached(&key, || {
+                run_script(
+                    &self.id,
+                    script,
+                    self.script_lang,
+                    Some(items.clone()),
+                    &self.limits,
+                )
+            })?);
         };
 
         Ok(state)
@@ -0,0 +1,41 @@
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+/// Lifecycle of a component
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Draft,
+    #[default]
+    Active,
+    Deprecated,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Draft => write!(f, "draft"),
+            Self::Active => write!(f, "active"),
+            Self::Deprecated => write!(f, "deprecated"),
+        }
+    }
+}
+
+/// Optional information about a component, which doesn't change its expansion
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Metadata {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub status: Status,
+}
+
+impl Metadata {
+    /// Whether the component has at least one of `tags`, or `tags` is empty
+    pub fn matches_tags(&self, tags: &[String]) -> bool {
+        tags.is_empty() || tags.iter().any(|tag| self.tags.contains(tag))
+    }
+}
@@ -1,10 +1,19 @@
 use std::fmt::Display;
 
 mod list;
-use list::*;
+pub use list::List;
+
+mod limits;
+pub use limits::Limits;
+
+mod metadata;
+pub use metadata::{Metadata, Status};
+
+mod script_lang;
+pub use script_lang::ScriptLang;
 
 mod generator;
-use generator::*;
+pub use generator::Generator;
 
 mod query;
 pub use query::Query;
@@ -23,11 +32,39 @@ pub enum Q3Components {
     Generator(Generator),
 }
 
+impl Q3Components {
+    /// Name of the kind of component, as used in the q3 file
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::List(_) => "list",
+            Self::Query(_) => "query",
+            Self::Generator(_) => "generator",
+        }
+    }
+
+    /// Ids referenced by the component, in order of first appearance
+    pub fn dependencies(&self) -> Vec<Id> {
+        match self {
+            Self::Query(query) => query.dependencies(),
+            Self::List(_) | Self::Generator(_) => Vec::new(),
+        }
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        match self {
+            Self::List(list) => &list.metadata,
+            Self::Query(query) => query.metadata(),
+            Self::Generator(generator) => &generator.metadata,
+        }
+    }
+}
+
 impl TryFrom<(Id, ListConfig)> for Q3Components {
     type Error = Q3Error;
 
     fn try_from(value: (Id, ListConfig)) -> Result<Self, Self::Error> {
         let (id, config) = value;
+        config.limits.validate(&id)?;
 
         let value = match config.data {
             crate::config::PathOrValue::File(path) => std::fs::read_to_string(path)
@@ -40,6 +77,10 @@ impl TryFrom<(Id, ListConfig)> for Q3Components {
             value,
             separator: config.separator,
             script: config.script,
+            script_lang: config.script_lang,
+            output: None,
+            limits: config.limits,
+            metadata: config.metadata,
         }))
     }
 }
@@ -49,11 +90,15 @@ impl TryFrom<(Id, GeneratorConfig)> for Q3Components {
 
     fn try_from(value: (Id, GeneratorConfig)) -> Result<Self, Self::Error> {
         let (id, config) = value;
+        config.limits.validate(&id)?;
 
         Ok(Self::Generator(Generator {
             id,
             value: None,
             script: config.script,
+            script_lang: config.script_lang,
+            limits: config.limits,
+            metadata: config.metadata,
         }))
     }
 }
@@ -77,7 +122,7 @@ impl TryFrom<(Id, QueryConfig)> for Q3Components {
 
     fn try_from(value: (Id, QueryConfig)) -> Result<Self, Self::Error> {
         let (id, config) = value;
-        let query: Query = Query::new(id.0, config.value)?;
+        let query: Query = Query::new(id.0, config.value)?.with_metadata(config.metadata);
 
         Ok(Q3Components::Query(query))
     }
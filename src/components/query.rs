@@ -1,4 +1,4 @@
-use super::{Id, Identify};
+use super::{Id, Identify, Metadata};
 
 use std::fmt::Display;
 
@@ -15,12 +15,14 @@ pub enum Query {
         id: Id,
         query: String,
         tokens: Vec<Q3Ast>,
+        metadata: Metadata,
     },
     /// A query that as been expanded. All nested queries have been expanded.
     Expanded {
         id: Id,
         tokens: Vec<Q3Ast>,
         query: String,
+        metadata: Metadata,
     },
 }
 
@@ -43,15 +45,50 @@ impl Query {
                 id,
                 query,
                 tokens: query_components,
+                metadata: Metadata::default(),
             })
         } else {
             Ok(Self::Raw {
                 id,
                 query,
                 tokens: query_components,
+                metadata: Metadata::default(),
             })
         }
     }
+
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        match &mut self {
+            Self::Raw { metadata: m, .. } | Self::Expanded { metadata: m, .. } => *m = metadata,
+        }
+
+        self
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        match self {
+            Self::Raw { metadata, .. } | Self::Expanded { metadata, .. } => metadata,
+        }
+    }
+
+    /// Ids referenced by a query that has not been expanded yet
+    pub fn dependencies(&self) -> Vec<Id> {
+        let mut dependencies: Vec<Id> = Vec::new();
+
+        if let Self::Raw { tokens, .. } = self {
+            for token in tokens {
+                if let Q3Ast::Id(id) = token {
+                    let id = Id(id.clone());
+
+                    if !dependencies.contains(&id) {
+                        dependencies.push(id);
+                    }
+                }
+            }
+        }
+
+        dependencies
+    }
 }
 
 impl Identify for Query {
@@ -84,7 +121,10 @@ impl Expand for Query {
     fn expand(&mut self, mut state: Self::State) -> Result<Self::State, Q3Error> {
         match self {
             Query::Raw {
-                id, ref mut tokens, ..
+                id,
+                ref mut tokens,
+                metadata,
+                ..
             } => {
                 state.set_failed_expansion(id.clone())?;
                 state = tokens.expand(state)?;
@@ -94,6 +134,7 @@ impl Expand for Query {
 
                     *self = Query::Expanded {
                         id: id.to_owned(),
+                        metadata: metadata.clone(),
                         tokens: tokens.to_vec(),
                         query: tokens
                             .iter()
@@ -117,6 +158,9 @@ impl Expand for Vec<Q3Ast> {
         for token in self.iter_mut() {
             match token {
                 Q3Ast::Other(_) => (),
+                Q3Ast::Id(id) if state.is_failed(&Id(id.to_string())) => {
+                    return Err(Q3Error::DependencyFailed(id.to_string()))
+                }
                 Q3Ast::Id(id) => match state.get(id.to_string()) {
                     Some(Q3Components::Query(expanded_query @ Query::Expanded { .. })) => {
                         *token = Q3Ast::Other(expanded_query.to_string())
@@ -239,3 +283,32 @@ fn test_query_expansion_cycle() {
 
     assert!(store.expand().is_err())
 }
+
+#[test]
+fn test_query_expansion_keeps_going() {
+    let mut store = QStore::new();
+
+    let q1 = Query::new("q1", "lorem #{missing}").unwrap();
+    let q2 = Query::new("q2", "dolor #{q1}").unwrap();
+    let q3 = Query::new("q3", "sit #{q4}").unwrap();
+    let q4 = Query::new("q4", "amet").unwrap();
+
+    store.insert(Q3Components::Query(q1));
+    store.insert(Q3Components::Query(q2));
+    store.insert(Q3Components::Query(q3));
+    store.insert(Q3Components::Query(q4));
+
+    let errors: Vec<(String, String)> = store
+        .expand_all()
+        .into_iter()
+        .map(|(id, err)| (id.to_string(), err.to_string()))
+        .collect();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].0, "q1");
+    assert!(errors[0].1.contains("missing"));
+    assert_eq!(errors[1].0, "q2");
+
+    assert!(store.is_failed(&Id("q2".into())));
+    assert_eq!(store.get("q3").unwrap().to_string(), "sit amet");
+}
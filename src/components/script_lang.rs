@@ -0,0 +1,21 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Language the script of a list or generator is written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptLang {
+    #[default]
+    Python,
+    Rhai,
+}
+
+impl Display for ScriptLang {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Python => write!(f, "python"),
+            Self::Rhai => write!(f, "rhai"),
+        }
+    }
+}
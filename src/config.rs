@@ -1,12 +1,14 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use serde::Deserialize;
 
 use crate::{Id, Q3Error, QStore};
 
-use crate::components::Q3Components;
+use crate::components::{Limits, Metadata, Q3Components, ScriptLang};
 
+/// Content of a q3 file
 #[derive(Debug, Deserialize)]
 pub struct Config {
     #[serde(rename = "query")]
@@ -15,11 +17,18 @@ pub struct Config {
     lists: Option<HashMap<String, ListConfig>>,
     #[serde(rename = "generator")]
     generators: Option<HashMap<String, GeneratorConfig>>,
+    #[serde(rename = "evaluation")]
+    pub evaluations: Option<HashMap<String, EvaluationConfig>>,
+    pub output: Option<OutputConfig>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct QueryConfig {
     pub value: String,
+    /// Path the query is written to by `build --out-dir`, relative to the output directory
+    pub output: Option<PathBuf>,
+    #[serde(flatten)]
+    pub metadata: Metadata,
 }
 
 #[derive(Debug, Deserialize)]
@@ -28,6 +37,12 @@ pub struct ListConfig {
     pub data: PathOrValue,
     pub separator: String,
     pub script: Option<String>,
+    #[serde(default)]
+    pub script_lang: ScriptLang,
+    #[serde(flatten)]
+    pub limits: Limits,
+    #[serde(flatten)]
+    pub metadata: Metadata,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,31 +55,94 @@ pub enum PathOrValue {
 #[derive(Debug, Deserialize)]
 pub struct GeneratorConfig {
     pub script: String,
+    #[serde(default)]
+    pub script_lang: ScriptLang,
+    #[serde(flatten)]
+    pub limits: Limits,
+    #[serde(flatten)]
+    pub metadata: Metadata,
 }
 
-impl TryFrom<Config> for QStore {
-    type Error = Q3Error;
+/// Relevance judgements of a query
+#[derive(Debug, Deserialize)]
+pub struct EvaluationConfig {
+    /// Ids of the corpus documents the query should match
+    pub relevant: Vec<String>,
+}
 
-    fn try_from(config: Config) -> Result<Self, Self::Error> {
-        let mut qstore = QStore::new();
+/// How `build` renders the expanded queries
+#[derive(Debug, Deserialize)]
+pub struct OutputConfig {
+    /// Template the queries are rendered with, see [`crate::render::Template`]
+    pub template: Option<String>,
+}
 
-        if let Some(lists) = config.lists {
-            for (id, list_config) in lists {
-                let list: Q3Components = ((Id(id), list_config)).try_into()?;
-                qstore.insert(list);
-            }
+impl Config {
+    /// Reads a q3 file
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Q3Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Files the lists of the config are read from
+    pub fn list_files(&self) -> Vec<PathBuf> {
+        self.lists
+            .iter()
+            .flat_map(|lists| lists.values())
+            .filter_map(|list| match &list.data {
+                PathOrValue::File(path) => Some(path.clone()),
+                PathOrValue::Value(_) => None,
+            })
+            .collect()
+    }
+
+    /// Output paths set on the queries of the config
+    pub fn outputs(&self) -> HashMap<Id, PathBuf> {
+        self.queries
+            .iter()
+            .filter_map(|(id, query)| Some((Id(id.clone()), query.output.clone()?)))
+            .collect()
+    }
+
+    /// Converts every entry of the config into a component, without stopping at the first
+    /// invalid entry
+    pub fn components(self) -> Vec<(Id, Result<Q3Components, Q3Error>)> {
+        let mut components = Vec::new();
+
+        for (id, list_config) in self.lists.unwrap_or_default() {
+            let id = Id(id);
+            components.push((id.clone(), (id, list_config).try_into()));
         }
 
-        if let Some(generators) = config.generators {
-            for (id, generator_config) in generators {
-                let generator: Q3Components = ((Id(id), generator_config)).try_into()?;
-                qstore.insert(generator);
-            }
+        for (id, generator_config) in self.generators.unwrap_or_default() {
+            let id = Id(id);
+            components.push((id.clone(), (id, generator_config).try_into()));
         }
 
-        for (id, query) in config.queries {
-            let query: Q3Components = ((Id(id), query)).try_into()?;
-            qstore.insert(query);
+        for (id, query_config) in self.queries {
+            let id = Id(id);
+            components.push((id.clone(), (id, query_config).try_into()));
+        }
+
+        components
+    }
+}
+
+impl FromStr for Config {
+    type Err = Q3Error;
+
+    fn from_str(config: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(config)?)
+    }
+}
+
+impl TryFrom<Config> for QStore {
+    type Error = Q3Error;
+
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let mut qstore = QStore::new();
+
+        for (_id, component) in config.components() {
+            qstore.insert(component?);
         }
 
         Ok(qstore)
@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+
+use crate::{Expand, Id, Q3Components, Q3Error, QStore};
+
+/// Expansions of a query where one of the lists it references is altered
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListVariants {
+    /// The query expanded with the whole list
+    pub full: String,
+    pub items: Vec<ItemVariants>,
+}
+
+/// Expansions of a query for a single list item
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemVariants {
+    pub item: String,
+    /// The query expanded with a list restricted to the item
+    pub only: String,
+    /// The query expanded with a list without the item, `None` if the item is the only one
+    pub without: Option<String>,
+}
+
+/// Expands the query `query_id` of an unexpanded store once with the whole list `list_id`, then
+/// once per list item with the list restricted to the item and once without it.
+///
+/// Only the components `query_id` references are expanded, and those that don't depend on the
+/// list are expanded once for all variants.
+pub fn list_variants(store: &QStore, query_id: &Id, list_id: &Id) -> Result<ListVariants, Q3Error> {
+    let list = match store.get(list_id.to_string()) {
+        Some(Q3Components::List(list)) => list,
+        _ => return Err(Q3Error::IdNotFound(list_id.to_string())),
+    };
+
+    let dependencies = store.dependencies_of(query_id);
+
+    if !dependencies.contains(list_id) {
+        return Err(Q3Error::NotADependency(
+            list_id.to_string(),
+            query_id.to_string(),
+        ));
+    }
+
+    let mut unchanged = QStore::new();
+    for id in &dependencies {
+        if let Some(component) = store.components.get(id) {
+            unchanged.insert(component.clone());
+        }
+    }
+
+    // Components depending on the list are expanded again for every variant
+    let affected = unchanged.affected_by(&HashSet::from([list_id.clone()]));
+    unchanged.components.retain(|id, _| !affected.contains(id));
+
+    let state = unchanged.clone();
+    Expand::expand(&mut unchanged, state)?;
+
+    let items = list.items();
+
+    let expand_with = |items: &[&str]| -> Result<String, Q3Error> {
+        let mut variant = unchanged.clone();
+        for id in &affected {
+            if let Some(component) = store.components.get(id) {
+                variant.insert(component.clone());
+            }
+        }
+        variant.insert(Q3Components::List(list.with_items(items)));
+        let state = variant.clone();
+        Expand::expand(&mut variant, state)?;
+
+        match variant.get(query_id.to_string()) {
+            Some(query @ Q3Components::Query(_)) => Ok(query.to_string()),
+            _ => Err(Q3Error::IdNotFound(query_id.to_string())),
+        }
+    };
+
+    let mut variants = Vec::with_capacity(items.len());
+
+    for (index, item) in items.iter().enumerate() {
+        let others: Vec<&str> = items
+            .iter()
+            .enumerate()
+            .filter(|(other_index, _)| *other_index != index)
+            .map(|(_, other)| *other)
+            .collect();
+
+        variants.push(ItemVariants {
+            item: item.to_string(),
+            only: expand_with(&[item])?,
+            without: match others.is_empty() {
+                true => None,
+                false => Some(expand_with(&others)?),
+            },
+        });
+    }
+
+    Ok(ListVariants {
+        full: expand_with(&items)?,
+        items: variants,
+    })
+}
+
+#[test]
+fn test_list_variants() {
+    use crate::components::{Limits, List, Metadata, ScriptLang};
+    use crate::Query;
+
+    let mut store = QStore::new();
+
+    store.insert(Q3Components::List(List {
+        id: Id("terms".into()),
+        value: "lorem,ipsum,dolor".into(),
+        separator: ",".into(),
+        script: None,
+        script_lang: ScriptLang::default(),
+        output: None,
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+    store.insert(Q3Components::Query(
+        Query::new("query", "title:(#{terms})").unwrap(),
+    ));
+
+    store.insert(Q3Components::List(List {
+        id: Id("other".into()),
+        value: "sit".into(),
+        separator: ",".into(),
+        script: None,
+        script_lang: ScriptLang::default(),
+        output: None,
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+    store.insert(Q3Components::Query(
+        Query::new("outer", "#{query} AND year:2020").unwrap(),
+    ));
+    // Components the query doesn't reference are left alone, even when they can't be expanded
+    store.insert(Q3Components::Query(
+        Query::new("recursive", "#{recursive} #{other}").unwrap(),
+    ));
+
+    let variants = list_variants(&store, &Id("query".into()), &Id("terms".into())).unwrap();
+
+    assert_eq!(variants.full, "title:(lorem,ipsum,dolor)");
+    assert_eq!(variants.items[1].item, "ipsum");
+    assert_eq!(variants.items[1].only, "title:(ipsum)");
+    assert_eq!(
+        variants.items[1].without.as_deref(),
+        Some("title:(lorem,dolor)")
+    );
+
+    let variants = list_variants(&store, &Id("outer".into()), &Id("terms".into())).unwrap();
+    assert_eq!(variants.items[0].only, "title:(lorem) AND year:2020");
+
+    assert!(matches!(
+        list_variants(&store, &Id("query".into()), &Id("other".into())),
+        Err(Q3Error::NotADependency(..))
+    ));
+}
@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+
+/// Precision and recall of a query against relevance judgements
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+    /// Matched documents that are not relevant
+    pub false_positives: Vec<String>,
+    /// Relevant documents that are not matched
+    pub false_negatives: Vec<String>,
+}
+
+impl Evaluation {
+    pub fn new(matched: &[&str], relevant: &[String]) -> Self {
+        let relevant_ids: HashSet<&str> = relevant.iter().map(|id| id.as_str()).collect();
+        let matched_ids: HashSet<&str> = matched.iter().copied().collect();
+
+        let true_positives = matched_ids.intersection(&relevant_ids).count();
+
+        let ratio = |count: usize, total: usize| match total {
+            0 => 0.0,
+            total => count as f64 / total as f64,
+        };
+
+        let precision = ratio(true_positives, matched_ids.len());
+        let recall = ratio(true_positives, relevant_ids.len());
+        let f1 = match precision + recall {
+            sum if sum > 0.0 => 2.0 * precision * recall / sum,
+            _ => 0.0,
+        };
+
+        Self {
+            precision,
+            recall,
+            f1,
+            false_positives: matched
+                .iter()
+                .filter(|id| !relevant_ids.contains(*id))
+                .map(|id| id.to_string())
+                .collect(),
+            false_negatives: relevant
+                .iter()
+                .filter(|id| !matched_ids.contains(id.as_str()))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[test]
+fn test_evaluation() {
+    let evaluation = Evaluation::new(
+        &["d1", "d2", "d3", "d4"],
+        &["d1".into(), "d2".into(), "d5".into()],
+    );
+
+    assert_eq!(evaluation.precision, 0.5);
+    assert_eq!(evaluation.recall, 2.0 / 3.0);
+    assert!((evaluation.f1 - 4.0 / 7.0).abs() < 1e-9);
+    assert_eq!(evaluation.false_positives, vec!["d3", "d4"]);
+    assert_eq!(evaluation.false_negatives, vec!["d5"]);
+
+    let evaluation = Evaluation::new(&[], &["d1".into()]);
+
+    assert_eq!(evaluation.precision, 0.0);
+    assert_eq!(evaluation.f1, 0.0);
+}
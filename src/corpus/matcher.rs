@@ -0,0 +1,375 @@
+use super::{tokenize, Corpus, Document, Field};
+use crate::parser::QueryAst;
+
+impl Corpus {
+    /// Returns the documents matching a query
+    pub fn search(&self, query: &QueryAst) -> Vec<&Document> {
+        self.documents
+            .iter()
+            .filter(|document| document.matches(query, &self.default_fields))
+            .collect()
+    }
+}
+
+impl Document {
+    /// Evaluates a query against the document with a minimal Lucene-like semantic.
+    ///
+    /// Clauses that don't target a field are evaluated against `default_fields`, or against every
+    /// field if it is empty. Boosts are ignored.
+    pub fn matches(&self, query: &QueryAst, default_fields: &[String]) -> bool {
+        let fields: Vec<&Field> = match default_fields.is_empty() {
+            true => self.fields.values().collect(),
+            false => default_fields
+                .iter()
+                .filter_map(|name| self.fields.get(name))
+                .collect(),
+        };
+
+        self.matches_fields(query, &fields)
+    }
+
+    fn matches_fields(&self, query: &QueryAst, fields: &[&Field]) -> bool {
+        match query {
+            QueryAst::Term(term) => fields.iter().any(|field| match_term(field, term)),
+            QueryAst::Phrase(phrase) => fields
+                .iter()
+                .any(|field| match_phrase(field, &tokenize(phrase), 0)),
+            QueryAst::Range(range) => fields.iter().any(|field| match_range(field, range)),
+            QueryAst::Field { name, query } => {
+                let fields: Vec<&Field> = match name.as_str() {
+                    "*" => self.fields.values().collect(),
+                    name => self.fields.get(name).into_iter().collect(),
+                };
+
+                self.matches_fields(query, &fields)
+            }
+            QueryAst::And(clauses) => self.matches_and(clauses, fields),
+            QueryAst::Or(clauses) => clauses
+                .iter()
+                .any(|clause| self.matches_fields(clause, fields)),
+            QueryAst::Sequence(clauses) => self.matches_sequence(clauses, fields),
+            // A query made only of prohibited clauses matches nothing, like in Lucene
+            QueryAst::Not(_) => false,
+            QueryAst::Required(query) | QueryAst::Group(query) => {
+                self.matches_fields(query, fields)
+            }
+            QueryAst::Proximity { query, distance } => match query.as_ref() {
+                QueryAst::Phrase(phrase) => fields.iter().any(|field| {
+                    match_phrase(field, &tokenize(phrase), distance.unwrap_or(0) as usize)
+                }),
+                QueryAst::Term(term) => fields
+                    .iter()
+                    .any(|field| match_fuzzy(field, term, distance.unwrap_or(2) as usize)),
+                query => self.matches_fields(query, fields),
+            },
+            QueryAst::Boost { query, .. } => self.matches_fields(query, fields),
+        }
+    }
+
+    /// Clauses joined by `AND` must all match, except `NOT` clauses which must not match. At
+    /// least one clause must not be negated.
+    fn matches_and(&self, clauses: &[QueryAst], fields: &[&Field]) -> bool {
+        let mut has_positive = false;
+
+        for clause in clauses {
+            let matches = match clause {
+                QueryAst::Not(query) => !self.matches_fields(query, fields),
+                clause => {
+                    has_positive = true;
+                    self.matches_fields(clause, fields)
+                }
+            };
+
+            if !matches {
+                return false;
+            }
+        }
+
+        has_positive
+    }
+
+    /// Juxtaposed clauses follow the semantic of a Lucene boolean query: required clauses must
+    /// match, prohibited clauses must not match, and at least one of the other clauses must match
+    /// when there is no required clause. Prohibited clauses alone match nothing.
+    fn matches_sequence(&self, clauses: &[QueryAst], fields: &[&Field]) -> bool {
+        let mut has_required = false;
+        let mut optional_clauses: Vec<&QueryAst> = Vec::new();
+
+        for clause in clauses {
+            match clause {
+                QueryAst::Required(query) => {
+                    has_required = true;
+
+                    if !self.matches_fields(query, fields) {
+                        return false;
+                    }
+                }
+                QueryAst::Not(query) => {
+                    if self.matches_fields(query, fields) {
+                        return false;
+                    }
+                }
+                clause => optional_clauses.push(clause),
+            }
+        }
+
+        has_required
+            || optional_clauses
+                .iter()
+                .any(|clause| self.matches_fields(clause, fields))
+    }
+}
+
+/// A character of a wildcard pattern
+#[derive(Debug, PartialEq)]
+enum Wildcard {
+    /// `*`, any number of characters
+    Any,
+    /// `?`, exactly one character
+    One,
+    Char(char),
+}
+
+/// Parses a lowercased wildcard pattern, escaped `*` and `?` being literal characters. Returns
+/// `None` when the term has no wildcard.
+fn wildcard_pattern(term: &str) -> Option<Vec<Wildcard>> {
+    let mut pattern = Vec::with_capacity(term.len());
+    let mut chars = term.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => pattern.push(Wildcard::Any),
+            '?' => pattern.push(Wildcard::One),
+            '\\' => pattern.extend(
+                chars
+                    .next()
+                    .into_iter()
+                    .flat_map(char::to_lowercase)
+                    .map(Wildcard::Char),
+            ),
+            c => pattern.extend(c.to_lowercase().map(Wildcard::Char)),
+        }
+    }
+
+    pattern
+        .iter()
+        .any(|c| matches!(c, Wildcard::Any | Wildcard::One))
+        .then_some(pattern)
+}
+
+fn unescape(term: &str) -> String {
+    let mut result = String::with_capacity(term.len());
+    let mut chars = term.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            c => result.push(c),
+        }
+    }
+
+    result
+}
+
+fn match_term(field: &Field, term: &str) -> bool {
+    if let Some(pattern) = wildcard_pattern(term) {
+        if pattern == [Wildcard::Any] {
+            return true;
+        }
+
+        return field.tokens.iter().any(|token| {
+            let token: Vec<char> = token.chars().collect();
+            match_wildcard(&token, &pattern)
+        });
+    }
+
+    match tokenize(&unescape(term).to_lowercase()).as_slice() {
+        [] => false,
+        [token] => field.tokens.contains(token),
+        tokens => match_phrase(field, tokens, 0),
+    }
+}
+
+/// Matches a token against a wildcard pattern, backtracking only to the last `*`
+fn match_wildcard(token: &[char], pattern: &[Wildcard]) -> bool {
+    let (mut t, mut p) = (0, 0);
+    // Pattern position after the last `*`, and the token position it currently matches up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < token.len() {
+        match pattern.get(p) {
+            Some(Wildcard::Any) => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(Wildcard::One) => {
+                t += 1;
+                p += 1;
+            }
+            Some(Wildcard::Char(c)) if *c == token[t] => {
+                t += 1;
+                p += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == Wildcard::Any)
+}
+
+/// Matches consecutive tokens, or tokens that all appear within a window of
+/// `tokens.len() + slop` tokens when `slop` isn't 0
+fn match_phrase(field: &Field, tokens: &[String], slop: usize) -> bool {
+    if tokens.is_empty() {
+        return false;
+    }
+
+    let window = tokens.len() + slop;
+
+    if slop == 0 {
+        return field.tokens.windows(window).any(|window| window == tokens);
+    }
+
+    (0..field.tokens.len()).any(|start| {
+        let window = &field.tokens[start..field.tokens.len().min(start + window)];
+        tokens.contains(&window[0]) && tokens.iter().all(|token| window.contains(token))
+    })
+}
+
+fn match_fuzzy(field: &Field, term: &str, distance: usize) -> bool {
+    let term: Vec<char> = unescape(term).to_lowercase().chars().collect();
+
+    field.tokens.iter().any(|token| {
+        let token: Vec<char> = token.chars().collect();
+        levenshtein(&token, &term) <= distance
+    })
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Matches `[lower TO upper]` ranges, `{}` bounds being exclusive. Bounds are compared as numbers
+/// when both the bound and the field parse as numbers, as strings otherwise.
+fn match_range(field: &Field, range: &str) -> bool {
+    let inclusive_lower = range.starts_with('[');
+    let inclusive_upper = range.ends_with(']');
+
+    let Some((lower, upper)) = range[1..range.len() - 1].split_once(" TO ") else {
+        return false;
+    };
+
+    let value = field.text.trim();
+    let compare = |bound: &str| match (value.parse::<f64>(), bound.parse::<f64>()) {
+        (Ok(value), Ok(bound)) => value.partial_cmp(&bound),
+        _ => Some(value.cmp(bound)),
+    };
+
+    let lower = lower.trim().trim_matches('"');
+    let upper = upper.trim().trim_matches('"');
+
+    let above = lower == "*"
+        || match compare(lower) {
+            Some(std::cmp::Ordering::Greater) => true,
+            Some(std::cmp::Ordering::Equal) => inclusive_lower,
+            _ => false,
+        };
+
+    let below = upper == "*"
+        || match compare(upper) {
+            Some(std::cmp::Ordering::Less) => true,
+            Some(std::cmp::Ordering::Equal) => inclusive_upper,
+            _ => false,
+        };
+
+    above && below
+}
+
+#[test]
+fn test_search() {
+    use crate::parser::parse_boolean_query;
+    use std::collections::HashMap;
+
+    let document = |id: &str, title: &str, year: &str| Document {
+        id: id.into(),
+        fields: HashMap::from([
+            ("title".to_string(), Field::from(title.to_string())),
+            ("year".to_string(), Field::from(year.to_string())),
+        ]),
+    };
+
+    let corpus = Corpus {
+        documents: vec![
+            document("d1", "Lorem ipsum dolor sit amet", "2001"),
+            document("d2", "Consectetur adipiscing elit", "2010"),
+            document("d3", "Ipsum, sed do lorem", "2020"),
+        ],
+        default_fields: vec!["title".into()],
+    };
+
+    let search = |query: &str| -> Vec<String> {
+        corpus
+            .search(&parse_boolean_query(query).unwrap())
+            .into_iter()
+            .map(|document| document.id.clone())
+            .collect()
+    };
+
+    assert_eq!(search("lorem"), vec!["d1", "d3"]);
+    assert_eq!(search("\"lorem ipsum\""), vec!["d1"]);
+    assert_eq!(search("\"lorem ipsum\"~2"), vec!["d1", "d3"]);
+    assert_eq!(search("lorem AND NOT sed"), vec!["d1"]);
+    assert_eq!(search("+ipsum -dolor"), vec!["d3"]);
+    assert_eq!(search("adipisc* OR sit"), vec!["d1", "d2"]);
+    assert_eq!(search("title:consectetur~1"), vec!["d2"]);
+    assert_eq!(search("year:[2005 TO *] lorem"), vec!["d1", "d2", "d3"]);
+    assert_eq!(search("+year:{2001 TO 2020] +lorem"), vec!["d3"]);
+    assert!(search("-lorem").is_empty());
+    assert!(search("-lorem -sed").is_empty());
+    assert!(search("NOT lorem AND NOT sed").is_empty());
+}
+
+#[test]
+fn test_match_wildcard() {
+    let matches = |token: &str, pattern: &str| {
+        let token: Vec<char> = token.chars().collect();
+        match_wildcard(&token, &wildcard_pattern(pattern).unwrap())
+    };
+
+    assert!(matches("lorem", "lor?m"));
+    assert!(matches("lorem", "l*"));
+    assert!(matches("café", "caf?"));
+    assert!(matches("naïve", "na?ve"));
+    assert!(!matches("café", "caf??"));
+    assert!(!matches("lorem", "lor?"));
+    assert!(matches("lorem", "*o*e*"));
+    assert!(matches("lorem", "l*m"));
+    assert!(!matches("lorem", "l*o"));
+    assert!(matches("a*b", "a\\**"));
+    assert!(!matches("axb", "a\\**"));
+    assert_eq!(wildcard_pattern("a\\*b"), None);
+
+    let token: Vec<char> = "a".repeat(64).chars().collect();
+    let pattern = wildcard_pattern(&format!("{}b", "*a".repeat(32))).unwrap();
+    assert!(!match_wildcard(&token, &pattern));
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::Q3Error;
+
+mod evaluation;
+pub use evaluation::Evaluation;
+
+mod matcher;
+
+/// A document of a local corpus
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub id: String,
+    pub fields: HashMap<String, Field>,
+}
+
+/// The content of a document field, with its lowercased tokens
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub text: String,
+    pub tokens: Vec<String>,
+}
+
+impl From<String> for Field {
+    fn from(text: String) -> Self {
+        Self {
+            tokens: tokenize(&text),
+            text,
+        }
+    }
+}
+
+/// A set of documents queries can be evaluated against, without a search engine
+#[derive(Debug, Clone, PartialEq)]
+pub struct Corpus {
+    pub documents: Vec<Document>,
+    /// Fields searched by clauses that don't target a field. Every field is searched if empty.
+    pub default_fields: Vec<String>,
+}
+
+/// Splits a text on non alphanumeric characters and lowercases the resulting tokens
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+fn field_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(boolean) => Some(boolean.to_string()),
+        Value::Array(values) => Some(
+            values
+                .iter()
+                .filter_map(field_text)
+                .collect::<Vec<String>>()
+                .join(" "),
+        ),
+        Value::Null | Value::Object(_) => None,
+    }
+}
+
+impl Corpus {
+    /// Reads a JSON lines file, one document per line.
+    ///
+    /// Only `fields` are loaded, unless it is empty, in which case every field is loaded.
+    pub fn from_path<P: AsRef<Path>>(
+        path: P,
+        id_field: &str,
+        fields: Vec<String>,
+    ) -> Result<Self, Q3Error> {
+        let data = std::fs::read_to_string(path)?;
+        let mut documents = Vec::new();
+
+        for (index, line) in data.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let json: HashMap<String, Value> = serde_json::from_str(line)
+                .map_err(|err| Q3Error::FailedToParseCorpusDocument(index + 1, err.to_string()))?;
+
+            let id = json.get(id_field).and_then(field_text).ok_or_else(|| {
+                Q3Error::FailedToParseCorpusDocument(
+                    index + 1,
+                    format!("missing `{id_field}` field"),
+                )
+            })?;
+
+            let fields = json
+                .iter()
+                .filter(|(name, _)| *name != id_field)
+                .filter(|(name, _)| fields.is_empty() || fields.contains(name))
+                .filter_map(|(name, value)| Some((name.clone(), field_text(value)?.into())))
+                .collect();
+
+            documents.push(Document { id, fields });
+        }
+
+        Ok(Self {
+            documents,
+            default_fields: fields,
+        })
+    }
+}
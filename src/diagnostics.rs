@@ -0,0 +1,233 @@
+use std::collections::{HashMap, HashSet};
+
+use thiserror::Error;
+
+use crate::components::Status;
+use crate::{Id, Q3Components, Q3Error};
+
+/// A problem found by the static analysis of a q3 file
+#[derive(Error, Debug)]
+pub enum Diagnostic {
+    #[error("{0}: {1}")]
+    InvalidComponent(Id, Q3Error),
+    #[error("{0}: id is defined more than once")]
+    DuplicateId(Id),
+    #[error("{0}: unknown reference `#{{{1}}}`")]
+    UnknownReference(Id, Id),
+    #[error("reference cycle {}", format_cycle(.0))]
+    Cycle(Vec<Id>),
+    #[error("{0}: {1} is never used")]
+    Unused(Id, &'static str),
+    #[error("{0}: references deprecated {1} `#{{{2}}}`")]
+    DeprecatedReference(Id, &'static str, Id),
+}
+
+fn format_cycle(cycle: &[Id]) -> String {
+    cycle
+        .iter()
+        .chain(cycle.first())
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>()
+        .join(" -> ")
+}
+
+impl Diagnostic {
+    /// Errors prevent the q3 file from being expanded, other diagnostics are warnings
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Self::Unused(..) | Self::DeprecatedReference(..))
+    }
+}
+
+/// Warns about active components referencing deprecated ones, sorted by id
+pub fn deprecated_references(store: &HashMap<Id, Q3Components>) -> Vec<Diagnostic> {
+    let mut ids: Vec<&Id> = store.keys().collect();
+    ids.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut diagnostics = Vec::new();
+
+    for id in ids {
+        if store[id].metadata().status != Status::Active {
+            continue;
+        }
+
+        for dependency in store[id].dependencies() {
+            match store.get(&dependency) {
+                Some(component) if component.metadata().status == Status::Deprecated => diagnostics
+                    .push(Diagnostic::DeprecatedReference(
+                        id.clone(),
+                        component.kind(),
+                        dependency,
+                    )),
+                _ => (),
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Analyzes components without expanding them, hence without running any script
+pub fn analyze(components: Vec<(Id, Result<Q3Components, Q3Error>)>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut store: HashMap<Id, Q3Components> = HashMap::new();
+    let mut defined: HashSet<Id> = HashSet::new();
+
+    for (id, component) in components {
+        if !defined.insert(id.clone()) {
+            diagnostics.push(Diagnostic::DuplicateId(id.clone()));
+        }
+
+        match component {
+            Ok(component) => {
+                store.entry(id).or_insert(component);
+            }
+            Err(err) => diagnostics.push(Diagnostic::InvalidComponent(id, err)),
+        }
+    }
+
+    let mut ids: Vec<&Id> = store.keys().collect();
+    ids.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut used: HashSet<Id> = HashSet::new();
+
+    for id in &ids {
+        for dependency in store[*id].dependencies() {
+            if !defined.contains(&dependency) {
+                diagnostics.push(Diagnostic::UnknownReference(
+                    (*id).clone(),
+                    dependency.clone(),
+                ));
+            }
+
+            used.insert(dependency);
+        }
+    }
+
+    diagnostics.extend(deprecated_references(&store));
+
+    for cycle in find_cycles(&store, &ids) {
+        diagnostics.push(Diagnostic::Cycle(cycle));
+    }
+
+    for id in &ids {
+        match &store[*id] {
+            component @ (Q3Components::List(_) | Q3Components::Generator(_))
+                if !used.contains(*id) =>
+            {
+                diagnostics.push(Diagnostic::Unused((*id).clone(), component.kind()))
+            }
+            _ => (),
+        }
+    }
+
+    diagnostics
+}
+
+/// Depth first search of the reference graph, returning each cycle once
+fn find_cycles(store: &HashMap<Id, Q3Components>, ids: &[&Id]) -> Vec<Vec<Id>> {
+    fn visit(
+        id: &Id,
+        store: &HashMap<Id, Q3Components>,
+        path: &mut Vec<Id>,
+        visited: &mut HashSet<Id>,
+        cycles: &mut Vec<Vec<Id>>,
+    ) {
+        if let Some(position) = path.iter().position(|elem| elem == id) {
+            let mut cycle = path[position..].to_vec();
+
+            let start = cycle
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.0.cmp(&b.0))
+                .map(|(index, _)| index)
+                .unwrap_or_default();
+            cycle.rotate_left(start);
+
+            if !cycles.contains(&cycle) {
+                cycles.push(cycle);
+            }
+
+            return;
+        }
+
+        if !visited.insert(id.clone()) {
+            return;
+        }
+
+        if let Some(component) = store.get(id) {
+            path.push(id.clone());
+
+            for dependency in component.dependencies() {
+                visit(&dependency, store, path, visited, cycles);
+            }
+
+            path.pop();
+        }
+    }
+
+    let mut cycles = Vec::new();
+    let mut visited = HashSet::new();
+
+    for id in ids {
+        visit(id, store, &mut Vec::new(), &mut visited, &mut cycles);
+    }
+
+    cycles
+}
+
+#[test]
+fn test_analyze() {
+    use crate::components::{Limits, List, Metadata, ScriptLang};
+    use crate::Query;
+
+    let list = |id: &str| {
+        Ok(Q3Components::List(List {
+            id: Id(id.into()),
+            value: String::new(),
+            separator: ",".into(),
+            script: None,
+            script_lang: ScriptLang::default(),
+            output: None,
+            limits: Limits::default(),
+            metadata: Metadata::default(),
+        }))
+    };
+    let query = |id: &str, value: &str| Ok(Q3Components::Query(Query::new(id, value).unwrap()));
+
+    let diagnostics: Vec<String> = analyze(vec![
+        (Id("used".into()), list("used")),
+        (Id("unused".into()), list("unused")),
+        (Id("q1".into()), query("q1", "#{used} #{q2} #{missing}")),
+        (Id("q2".into()), query("q2", "#{q3}")),
+        (Id("q3".into()), query("q3", "#{q1}")),
+        (Id("q3".into()), query("q3", "lorem")),
+        (
+            Id("old".into()),
+            list("old").map(|component| match component {
+                Q3Components::List(list) => Q3Components::List(List {
+                    metadata: Metadata {
+                        status: Status::Deprecated,
+                        ..Metadata::default()
+                    },
+                    ..list
+                }),
+                other => other,
+            }),
+        ),
+        (Id("q4".into()), query("q4", "#{old}")),
+    ])
+    .iter()
+    .map(|diagnostic| diagnostic.to_string())
+    .collect();
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            "q3: id is defined more than once",
+            "q1: unknown reference `#{missing}`",
+            "q4: references deprecated list `#{old}`",
+            "reference cycle q1 -> q2 -> q3 -> q1",
+            "unused: list is never used",
+        ]
+    );
+}
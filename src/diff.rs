@@ -0,0 +1,393 @@
+use std::collections::{BTreeMap, HashSet};
+
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+use crate::lock::LockFile;
+use crate::{Identify, Q3Components, QStore};
+
+/// Diff of two token sequences. Removed tokens are wrapped in `[-` `-]`, added tokens in `{+` `+}`,
+/// and tokens are joined back with single spaces.
+fn diff_tokens(old: &[&str], new: &[&str]) -> String {
+    let mut result: Vec<String> = Vec::new();
+
+    for op in capture_diff_slices(Algorithm::Myers, old, new) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        let removed = old[old_range].join(" ");
+        let added = new[new_range].join(" ");
+
+        match tag {
+            DiffTag::Equal => result.push(removed),
+            DiffTag::Delete => result.push(format!("[-{removed}-]")),
+            DiffTag::Insert => result.push(format!("{{+{added}+}}")),
+            DiffTag::Replace => result.push(format!("[-{removed}-]{{+{added}+}}")),
+        }
+    }
+
+    result.join(" ")
+}
+
+/// Word level diff of two texts. Removed words are wrapped in `[-` `-]`, added words in `{+` `+}`.
+///
+/// Words are compared and joined back with single spaces.
+pub fn word_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+
+    diff_tokens(&old, &new)
+}
+
+/// Splits a query into words, parentheses and quoted phrases
+fn tokenize(query: &str) -> Vec<&str> {
+    let mut tokens: Vec<&str> = Vec::new();
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+
+        match c {
+            c if c.is_whitespace() => continue,
+            '(' | ')' => (),
+            '"' => {
+                let mut escaped = false;
+
+                for (index, c) in chars.by_ref() {
+                    end = index + c.len_utf8();
+
+                    match c {
+                        '"' if !escaped => break,
+                        '\\' => escaped = !escaped,
+                        _ => escaped = false,
+                    }
+                }
+            }
+            _ => {
+                while let Some((index, c)) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                        break;
+                    }
+
+                    end = index + c.len_utf8();
+                    chars.next();
+                }
+            }
+        }
+
+        tokens.push(&query[start..end]);
+    }
+
+    tokens
+}
+
+/// Token level diff of two queries, keeping quoted phrases and parentheses as single tokens
+pub fn token_diff(old: &str, new: &str) -> String {
+    diff_tokens(&tokenize(old), &tokenize(new))
+}
+
+/// An expanded component of one version of a q3 file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    pub kind: String,
+    pub value: String,
+    /// Items of a list, unknown when a script transforms them or when read from a lock file
+    pub items: Option<Vec<String>>,
+    /// Ids of the components a query references
+    pub dependencies: Vec<String>,
+}
+
+/// Expanded components of one version of a q3 file, by id
+pub type Version = BTreeMap<String, Expansion>;
+
+/// Version of the successfully expanded components of `expanded`, where `raw` is the same store
+/// before expansion
+pub fn version(raw: &QStore, expanded: &QStore) -> Version {
+    expanded
+        .components
+        .iter()
+        .filter(|(id, _)| !expanded.is_failed(id))
+        .map(|(id, component)| {
+            let raw = raw.components.get(id).unwrap_or(component);
+
+            // Scripts can change a list without changing its items
+            let items = match raw {
+                Q3Components::List(list) if list.script.is_none() => {
+                    Some(list.items().into_iter().map(String::from).collect())
+                }
+                _ => None,
+            };
+
+            let expansion = Expansion {
+                kind: component.kind().into(),
+                value: component.to_string(),
+                items,
+                dependencies: raw.dependencies().into_iter().map(|id| id.0).collect(),
+            };
+
+            (component.get_id().to_string(), expansion)
+        })
+        .collect()
+}
+
+impl From<&LockFile> for Version {
+    fn from(lock: &LockFile) -> Self {
+        lock.components
+            .iter()
+            .map(|component| {
+                let expansion = Expansion {
+                    kind: component.kind.clone(),
+                    value: component.value.clone(),
+                    items: None,
+                    dependencies: component.dependencies.clone(),
+                };
+
+                (component.id.clone(), expansion)
+            })
+            .collect()
+    }
+}
+
+/// How a list used by a changed query changed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListChange {
+    Items {
+        id: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    /// Token diff of the expanded list, when its items are unknown
+    Value { id: String, diff: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryChange {
+    Added {
+        id: String,
+        query: String,
+    },
+    Removed {
+        id: String,
+    },
+    Changed {
+        id: String,
+        /// Token diff of the expanded query
+        diff: String,
+        /// Changed lists the query uses, directly or through nested queries
+        lists: Vec<ListChange>,
+    },
+}
+
+/// Lists a component of `version` uses, sorted by id
+fn lists(version: &Version, id: &str) -> Vec<String> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut pending: Vec<String> = vec![id.into()];
+    let mut lists: Vec<String> = Vec::new();
+
+    while let Some(id) = pending.pop() {
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+
+        if let Some(expansion) = version.get(&id) {
+            if expansion.kind == "list" {
+                lists.push(id);
+            } else {
+                pending.extend(expansion.dependencies.iter().cloned());
+            }
+        }
+    }
+
+    lists.sort();
+    lists
+}
+
+fn list_change(id: &str, old: &Expansion, new: &Expansion) -> Option<ListChange> {
+    match (&old.items, &new.items) {
+        (Some(old_items), Some(new_items)) if old_items != new_items => Some(ListChange::Items {
+            id: id.into(),
+            added: new_items
+                .iter()
+                .filter(|item| !old_items.contains(item))
+                .cloned()
+                .collect(),
+            removed: old_items
+                .iter()
+                .filter(|item| !new_items.contains(item))
+                .cloned()
+                .collect(),
+        }),
+        (Some(_), Some(_)) => None,
+        _ if old.value != new.value => Some(ListChange::Value {
+            id: id.into(),
+            diff: token_diff(&old.value, &new.value),
+        }),
+        _ => None,
+    }
+}
+
+/// Added, removed and changed queries between two versions of a q3 file, sorted by id
+pub fn diff_queries(old: &Version, new: &Version) -> Vec<QueryChange> {
+    let ids: HashSet<&String> = old
+        .iter()
+        .chain(new.iter())
+        .filter(|(_, expansion)| expansion.kind == "query")
+        .map(|(id, _)| id)
+        .collect();
+    let mut ids: Vec<&String> = ids.into_iter().collect();
+    ids.sort();
+
+    ids.into_iter()
+        .filter_map(|id| match (old.get(id), new.get(id)) {
+            (None, Some(new)) => Some(QueryChange::Added {
+                id: id.clone(),
+                query: new.value.clone(),
+            }),
+            (Some(_), None) => Some(QueryChange::Removed { id: id.clone() }),
+            (Some(old_query), Some(new_query)) if old_query.value != new_query.value => {
+                let lists = lists(new, id)
+                    .iter()
+                    .filter_map(|list| list_change(list, old.get(list)?, new.get(list)?))
+                    .collect();
+
+                Some(QueryChange::Changed {
+                    id: id.clone(),
+                    diff: token_diff(&old_query.value, &new_query.value),
+                    lists,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_word_diff() {
+    assert_eq!(
+        word_diff("lorem ipsum dolor", "lorem sit amet dolor"),
+        "lorem [-ipsum-]{+sit amet+} dolor"
+    );
+    assert_eq!(word_diff("lorem", "lorem  "), "lorem");
+}
+
+#[test]
+fn test_diff_queries() {
+    use crate::{Id, Limits, List, Metadata, Query, ScriptLang};
+
+    let store = |items: &str, queries: &[(&str, &str)]| {
+        let mut raw = QStore::new();
+        raw.insert(Q3Components::List(List {
+            id: Id("words".into()),
+            value: items.into(),
+            separator: " ".into(),
+            script: None,
+            script_lang: ScriptLang::default(),
+            output: None,
+            limits: Limits::default(),
+            metadata: Metadata::default(),
+        }));
+        for (id, query) in queries {
+            raw.insert(Q3Components::Query(Query::new(*id, *query).unwrap()));
+        }
+
+        let mut expanded = raw.clone();
+        expanded.expand_all();
+
+        version(&raw, &expanded)
+    };
+
+    assert_eq!(
+        tokenize(r#"title:("lorem \" ipsum" OR (dolor))"#),
+        vec![
+            "title:",
+            "(",
+            r#""lorem \" ipsum""#,
+            "OR",
+            "(",
+            "dolor",
+            ")",
+            ")"
+        ]
+    );
+
+    let old = store(
+        "lorem ipsum",
+        &[
+            ("nested", "(#{words})"),
+            ("title", "title:#{nested}"),
+            ("gone", "a"),
+        ],
+    );
+    let new = store(
+        "lorem dolor",
+        &[
+            ("nested", "(#{words})"),
+            ("title", "title:#{nested}"),
+            ("new", "b"),
+        ],
+    );
+
+    let changes = diff_queries(&old, &new);
+
+    assert_eq!(changes[0], QueryChange::Removed { id: "gone".into() });
+    assert_eq!(
+        changes[2],
+        QueryChange::Added {
+            id: "new".into(),
+            query: "b".into()
+        }
+    );
+    assert_eq!(
+        changes[3],
+        QueryChange::Changed {
+            id: "title".into(),
+            diff: "title: ( lorem [-ipsum-]{+dolor+} )".into(),
+            lists: vec![ListChange::Items {
+                id: "words".into(),
+                added: vec!["dolor".into()],
+                removed: vec!["ipsum".into()]
+            }]
+        }
+    );
+}
+
+#[test]
+fn test_diff_script_lists() {
+    use crate::{Id, Limits, List, Metadata, Query, ScriptLang};
+
+    crate::init_python().unwrap();
+
+    let store = |script: &str| {
+        let mut raw = QStore::new();
+        raw.insert(Q3Components::List(List {
+            id: Id("words".into()),
+            value: "lorem ipsum".into(),
+            separator: " ".into(),
+            script: Some(script.into()),
+            script_lang: ScriptLang::default(),
+            output: None,
+            limits: Limits::default(),
+            metadata: Metadata::default(),
+        }));
+        raw.insert(Q3Components::Query(
+            Query::new("title", "title:(#{words})").unwrap(),
+        ));
+
+        let mut expanded = raw.clone();
+        expanded.expand_all();
+
+        version(&raw, &expanded)
+    };
+
+    let old = store("value = ' OR '.join(value)");
+    let new = store("value = ' AND '.join(value)");
+
+    assert_eq!(
+        diff_queries(&old, &new),
+        vec![QueryChange::Changed {
+            id: "title".into(),
+            diff: "title: ( lorem [-OR-]{+AND+} ipsum )".into(),
+            lists: vec![ListChange::Value {
+                id: "words".into(),
+                diff: "lorem [-OR-]{+AND+} ipsum".into()
+            }]
+        }]
+    );
+}
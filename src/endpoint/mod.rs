@@ -0,0 +1,167 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::{Id, Q3Error};
+
+/// Flavour of the HTTP API of a search endpoint
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Engine {
+    /// `GET <endpoint>/select?q=<query>&rows=0`, reading `response.numFound`
+    Solr,
+    /// `POST <endpoint>/_count` with a `query_string` query, reading `count`
+    Elasticsearch,
+}
+
+/// A Solr or Elasticsearch compatible search endpoint
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    pub url: String,
+    pub engine: Engine,
+    agent: ureq::Agent,
+}
+
+impl Endpoint {
+    pub fn new<S: Into<String>>(url: S, engine: Engine, timeout: Duration) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            engine,
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+
+    /// Returns the number of documents matched by a query
+    pub fn count(&self, query: &str) -> Result<u64, Q3Error> {
+        let (response, pointer) = match self.engine {
+            Engine::Solr => (
+                self.agent
+                    .get(&format!("{}/select", self.url))
+                    .query("q", query)
+                    .query("rows", "0")
+                    .query("wt", "json")
+                    .call(),
+                "/response/numFound",
+            ),
+            Engine::Elasticsearch => (
+                self.agent
+                    .post(&format!("{}/_count", self.url))
+                    .send_json(json!({ "query": { "query_string": { "query": query } } })),
+                "/count",
+            ),
+        };
+
+        let response: Value = response
+            .map_err(request_error)?
+            .into_json()
+            .map_err(|err| Q3Error::SearchEndpointRequestFailed(err.to_string()))?;
+
+        response
+            .pointer(pointer)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| Q3Error::UnexpectedSearchEndpointResponse(pointer.to_string()))
+    }
+
+    /// Counts the hits of several queries, sending at most `jobs` requests at a time.
+    ///
+    /// Results are returned in the order of `queries`.
+    pub fn count_all(
+        &self,
+        queries: Vec<(Id, String)>,
+        jobs: usize,
+    ) -> Vec<(Id, Result<u64, Q3Error>)> {
+        let pending = Mutex::new(queries.into_iter().enumerate());
+        let results = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.max(1) {
+                scope.spawn(|| loop {
+                    let next = pending.lock().unwrap().next();
+
+                    let Some((index, (id, query))) = next else {
+                        break;
+                    };
+
+                    let count = self.count(&query);
+                    results.lock().unwrap().push((index, (id, count)));
+                });
+            }
+        });
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(index, _)| *index);
+
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
+
+fn request_error(err: ureq::Error) -> Q3Error {
+    match err {
+        ureq::Error::Status(status, _) => {
+            Q3Error::SearchEndpointRequestFailed(format!("HTTP status {status}"))
+        }
+        ureq::Error::Transport(transport) => {
+            Q3Error::SearchEndpointRequestFailed(match transport.message() {
+                Some(message) => format!("{}: {}", transport.kind(), message),
+                None => transport.kind().to_string(),
+            })
+        }
+    }
+}
+
+#[test]
+fn test_count_all() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/solr/core", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request_line = String::new();
+            let mut reader = BufReader::new(&stream);
+
+            reader.read_line(&mut request_line).unwrap();
+
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+
+                if header.trim().is_empty() {
+                    break;
+                }
+            }
+
+            let body = match request_line.contains("q=lorem") {
+                true => r#"{"response": {"numFound": 42}}"#,
+                false => r#"{"error": {"msg": "undefined field"}}"#,
+            };
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+
+    let endpoint = Endpoint::new(url, Engine::Solr, Duration::from_secs(5));
+    let results = endpoint.count_all(
+        vec![
+            (Id("q1".into()), "lorem".into()),
+            (Id("q2".into()), "ipsum".into()),
+            (Id("q3".into()), "lorem AND dolor".into()),
+        ],
+        2,
+    );
+
+    assert_eq!(results[0].0, Id("q1".into()));
+    assert_eq!(results[0].1.as_ref().unwrap(), &42);
+    assert!(results[1].1.is_err());
+    assert_eq!(results[2].1.as_ref().unwrap(), &42);
+}
@@ -6,6 +6,8 @@ use crate::components::Id;
 pub enum Q3Error {
     #[error("Failed to parse query")]
     FailedToParseQuery,
+    #[error("Failed to parse boolean query near `{0}`")]
+    FailedToParseBooleanQuery(String),
     #[error("Recursive query {0}")]
     FailedToExpand(Id),
     #[error("Failed to parse config file: {0}")]
@@ -14,8 +16,67 @@ pub enum Q3Error {
     FailedToReadDataFromDisk(#[from] std::io::Error),
     #[error("Id not found. Id {0} cannot be found in the store")]
     IdNotFound(String),
+    #[error("Dependency {0} failed to expand")]
+    DependencyFailed(String),
+    #[error("Failed to expand {} component(s):\n{}", .0.len(), format_failures(.0))]
+    ExpansionFailed(Vec<(Id, Q3Error)>),
     #[error("Variable `value` not assigned inside python script")]
     PythonScriptVariableNotAssigned,
+    #[error("Failed to parse corpus document at line {0}: {1}")]
+    FailedToParseCorpusDocument(usize, String),
+    #[error("Request to search endpoint failed: {0}")]
+    SearchEndpointRequestFailed(String),
+    #[error("Unexpected search endpoint response, `{0}` not found")]
+    UnexpectedSearchEndpointResponse(String),
+    #[error("Invalid output path {} for {0}: {2}", .1.display())]
+    InvalidOutputPath(Id, std::path::PathBuf, String),
+    #[error("Invalid lock file: {0}")]
+    InvalidLockFile(toml::de::Error),
+    #[error("Failed to write lock file: {0}")]
+    FailedToWriteLockFile(#[from] toml::ser::Error),
+    #[error("{} component(s) differ from the lock file:\n{}", .0.len(), format_differences(.0))]
+    LockMismatch(Vec<String>),
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+    #[error("Unknown transform `{0}`")]
+    UnknownTransform(String),
+    #[error("Component {0} is not a list")]
+    NotAList(String),
+    #[error("List {0} is not referenced by {1}")]
+    NotADependency(String, String),
+    #[error("Unknown REPL command `{0}`, type :help to list the commands")]
+    UnknownReplCommand(String),
+    #[error("Check failed with {0} error(s)")]
+    CheckFailed(usize),
     #[error("Python script failed: {0}")]
     PythonScriptFailed(#[from] pyo3::prelude::PyErr),
+    /// A script failed in a worker process, see [`crate::worker`]
+    #[error("Python script failed: {0}")]
+    WorkerScriptFailed(String),
+    #[error("Rhai script failed: {0}")]
+    RhaiScriptFailed(String),
+    #[error("Script worker failed: {0}")]
+    ScriptWorkerFailed(String),
+    #[error("Invalid limits for {0}: {1}")]
+    InvalidLimits(Id, String),
+    #[error("Script of {0} timed out after {1}s")]
+    ScriptTimedOut(Id, f64),
+    #[error("Script of {0} exceeded its memory limit of {1} MiB")]
+    ScriptMemoryLimitExceeded(Id, u64),
+}
+
+fn format_failures(failures: &[(Id, Q3Error)]) -> String {
+    failures
+        .iter()
+        .map(|(id, err)| format!("  {id}: {err}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn format_differences(differences: &[String]) -> String {
+    differences
+        .iter()
+        .map(|difference| format!("  {difference}"))
+        .collect::<Vec<String>>()
+        .join("\n")
 }
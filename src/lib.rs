@@ -0,0 +1,122 @@
+//! q³ builds search queries from lists, generators and nested queries.
+//!
+//! A q3 file is read into a [`Config`], converted into a [`QStore`] and expanded:
+//!
+//! ```no_run
+//! use q3::{Config, QStore};
+//!
+//! q3::init_python()?;
+//!
+//! let mut queries: QStore = Config::from_path("query.q3")?.try_into()?;
+//! let errors = queries.expand_all();
+//!
+//! for (id, err) in errors {
+//!     eprintln!("{id}: {err}");
+//! }
+//!
+//! println!("{}", queries.get("title").unwrap());
+//! # Ok::<(), q3::Q3Error>(())
+//! ```
+//!
+//! The items re-exported at the root of the crate and the [`parser`] module form the stable API.
+//! The other public modules back the `q3` command line tool and may change between releases.
+//! Those only the tool uses are behind the default `cli` feature, along with its dependencies.
+
+use pyo3::prelude::*;
+
+mod config;
+pub use config::Config;
+
+mod error;
+pub use error::Q3Error;
+
+mod expand;
+pub use expand::Expand;
+
+mod rhai_script;
+
+#[doc(hidden)]
+pub mod sandbox;
+
+#[doc(hidden)]
+pub mod script;
+
+mod components;
+pub use components::{
+    Generator, Id, Identify, Limits, List, Metadata, Q3Components, Query, ScriptLang, Status,
+};
+
+mod store;
+pub use store::QStore;
+
+pub mod parser;
+use parser::parse_query;
+
+#[doc(hidden)]
+pub mod cache;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod contribution;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod corpus;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod diagnostics;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod diff;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod endpoint;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod lock;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod output;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod render;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod repl;
+
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod report;
+
+#[doc(hidden)]
+pub mod trace;
+
+#[doc(hidden)]
+pub mod worker;
+
+/// Starts the Python interpreter and registers the `q3` module the scripts of lists and
+/// generators import their helpers from.
+///
+/// Must be called before the first script runs. Not needed when q3 is used from Python, where
+/// the `q3` package provides these helpers.
+pub fn init_python() -> Result<(), Q3Error> {
+    pyo3::prepare_freethreaded_python();
+
+    Python::with_gil(|py| {
+        let module = PyModule::new_bound(py, "q3")?;
+        script::add_helpers(&module)?;
+
+        py.import_bound("sys")?
+            .getattr("modules")?
+            .set_item("q3", module)?;
+
+        Ok(())
+    })
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::fnv1a;
+use crate::{Id, Identify, Q3Components, Q3Error, QStore, Query};
+
+/// Version of the lock file format
+pub const LOCK_VERSION: u32 = 1;
+
+/// Expanded components of a q3 file, with a hash of what they were expanded from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockFile {
+    pub version: u32,
+    #[serde(rename = "component", default)]
+    pub components: Vec<LockedComponent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedComponent {
+    pub id: String,
+    pub kind: String,
+    /// Hash of the data, separator, script and script language of a list, the script and script
+    /// language of a generator, or the template of a query along with the inputs of the
+    /// components it references
+    pub inputs: String,
+    /// Expanded value, the output of the script for lists and generators
+    pub value: String,
+    /// Ids of the components a query references
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+}
+
+fn inputs_hash(raw: &QStore, id: &Id, cache: &mut HashMap<Id, u64>) -> u64 {
+    if let Some(hash) = cache.get(id) {
+        return *hash;
+    }
+
+    // Guards against cycles, which fail to expand and are never locked anyway
+    cache.insert(id.clone(), 0);
+
+    let hash = match raw.components.get(id) {
+        Some(Q3Components::List(list)) => fnv1a(&[
+            "list",
+            &list.value,
+            &list.separator,
+            list.script.as_deref().unwrap_or_default(),
+            &list.script_lang.to_string(),
+        ]),
+        Some(Q3Components::Generator(generator)) => fnv1a(&[
+            "generator",
+            &generator.script,
+            &generator.script_lang.to_string(),
+        ]),
+        Some(Q3Components::Query(
+            query @ (Query::Raw {
+                query: template, ..
+            }
+            | Query::Expanded {
+                query: template, ..
+            }),
+        )) => {
+            let dependencies: Vec<String> = query
+                .dependencies()
+                .iter()
+                .map(|dependency| format!("{:016x}", inputs_hash(raw, dependency, cache)))
+                .collect();
+
+            let mut parts: Vec<&str> = vec!["query", template];
+            parts.extend(dependencies.iter().map(String::as_str));
+
+            fnv1a(&parts)
+        }
+        None => fnv1a(&["missing", &id.0]),
+    };
+
+    cache.insert(id.clone(), hash);
+    hash
+}
+
+impl LockFile {
+    /// Locks the components of `expanded`, hashing their inputs from `raw`, the same store
+    /// before expansion
+    pub fn new(raw: &QStore, expanded: &QStore) -> Self {
+        let mut cache: HashMap<Id, u64> = HashMap::new();
+
+        let mut components: Vec<LockedComponent> = expanded
+            .components
+            .values()
+            .map(|component| LockedComponent {
+                id: component.get_id().to_string(),
+                kind: component.kind().to_string(),
+                inputs: format!("{:016x}", inputs_hash(raw, component.get_id(), &mut cache)),
+                value: component.to_string(),
+                dependencies: raw
+                    .components
+                    .get(component.get_id())
+                    .map(|raw| raw.dependencies().into_iter().map(|id| id.0).collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        components.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Self {
+            version: LOCK_VERSION,
+            components,
+        }
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Q3Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Q3Error> {
+        Ok(std::fs::write(path, toml::to_string_pretty(self)?)?)
+    }
+
+    /// Describes how `current` differs from this lock file, sorted by id
+    pub fn differences(&self, current: &LockFile) -> Vec<String> {
+        let locked: HashMap<&str, &LockedComponent> = self
+            .components
+            .iter()
+            .map(|component| (component.id.as_str(), component))
+            .collect();
+
+        let mut differences: Vec<(String, String)> = Vec::new();
+
+        for component in &current.components {
+            let id = component.id.clone();
+
+            match locked.get(component.id.as_str()) {
+                None => differences.push((id, "not in the lock file".into())),
+                Some(locked) if locked.inputs != component.inputs => {
+                    differences.push((id, "inputs changed".into()))
+                }
+                Some(locked) if locked.value != component.value => {
+                    differences.push((id, "expands differently from the lock file".into()))
+                }
+                Some(_) => (),
+            }
+        }
+
+        for component in &self.components {
+            if !current
+                .components
+                .iter()
+                .any(|current| current.id == component.id)
+            {
+                differences.push((
+                    component.id.clone(),
+                    "is locked but no longer expands".into(),
+                ))
+            }
+        }
+
+        differences.sort();
+
+        differences
+            .into_iter()
+            .map(|(id, difference)| format!("{id}: {difference}"))
+            .collect()
+    }
+}
+
+impl FromStr for LockFile {
+    type Err = Q3Error;
+
+    fn from_str(lock: &str) -> Result<Self, Self::Err> {
+        toml::from_str(lock).map_err(Q3Error::InvalidLockFile)
+    }
+}
+
+#[test]
+fn test_lock_file() {
+    use crate::{Generator, Limits, List, Metadata, ScriptLang};
+
+    let mut raw = QStore::new();
+    raw.insert(Q3Components::List(List {
+        id: Id("words".into()),
+        value: "lorem ipsum".into(),
+        separator: " ".into(),
+        script: None,
+        script_lang: ScriptLang::default(),
+        output: None,
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+    raw.insert(Q3Components::Query(
+        Query::new("title", "title:(#{words})").unwrap(),
+    ));
+
+    let mut expanded = raw.clone();
+    expanded.expand_all();
+
+    let lock = LockFile::new(&raw, &expanded);
+    let serialized = toml::to_string_pretty(&lock).unwrap();
+
+    assert_eq!(toml::from_str::<LockFile>(&serialized).unwrap(), lock);
+    assert_eq!(lock.components[0].value, "title:(lorem ipsum)");
+    assert!(lock.differences(&lock).is_empty());
+
+    let mut changed = raw.clone();
+    changed.insert(Q3Components::List(List {
+        id: Id("words".into()),
+        value: "dolor".into(),
+        separator: " ".into(),
+        script: None,
+        script_lang: ScriptLang::default(),
+        output: None,
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+    let mut changed_expanded = changed.clone();
+    changed_expanded.expand_all();
+
+    assert_eq!(
+        lock.differences(&LockFile::new(&changed, &changed_expanded)),
+        vec!["title: inputs changed", "words: inputs changed"]
+    );
+
+    // The same script may mean something else in another language
+    let mut rhai = raw.clone();
+    rhai.insert(Q3Components::Generator(Generator {
+        id: Id("year".into()),
+        script: "value = \"2020\"".into(),
+        script_lang: ScriptLang::Rhai,
+        value: None,
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+    let mut python = rhai.clone();
+    if let Some(Q3Components::Generator(mut generator)) = python.get("year") {
+        generator.script_lang = ScriptLang::Python;
+        python.insert(Q3Components::Generator(generator));
+    }
+
+    let (rhai_lock, python_lock) = (LockFile::new(&rhai, &rhai), LockFile::new(&python, &python));
+    assert_eq!(
+        python_lock.differences(&rhai_lock),
+        vec!["year: inputs changed"]
+    );
+
+    let mut tampered = lock.clone();
+    tampered.components[0].value = "title:(dolor)".into();
+
+    assert_eq!(
+        tampered.differences(&lock),
+        vec!["title: expands differently from the lock file"]
+    );
+}
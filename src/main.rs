@@ -1,78 +1,79 @@
-#![feature(iter_intersperse)]
+use std::process::ExitCode;
 
 use clap::Parser;
-
-mod config;
-use config::Config;
-
-mod error;
-use error::Q3Error;
-
-mod expand;
-use expand::*;
-
-mod script;
-use script::q3;
-
-mod components;
-use components::*;
-
-mod store;
-use store::*;
+use q3::cache::{Cache, CACHE_DIR};
+use q3::sandbox::{self, Sandbox};
+use q3::worker::{self, WorkerCommand};
 
 mod cli;
-use cli::Cli;
-
-mod tui;
-use tui::*;
-
-use tabled::Table;
+use cli::{Cli, Command};
 
-mod parser;
-use crate::parser::parse_query;
+mod commands;
 
-use tabled::settings::object::Rows;
-use tabled::settings::Style;
-use tabled::settings::{measurement::Percent, Width};
-
-use tabled::settings::{
-    peaker::{PriorityMax, PriorityMin},
-    Settings, Padding,
-};
+mod tui;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    pyo3::append_to_inittab!(q3);
+fn main() -> ExitCode {
     let args = Cli::parse();
 
-    let config = std::fs::read_to_string(args.nsq)?;
-    let config: Config = toml::from_str(&config)?;
-    let mut queries: QStore = config.try_into()?;
-
-    queries.expand()?;
-
-    let mut table_data: Vec<TableRow> = queries.into();
-
-    let mut table = if let Some(id) = args.get {
-        table_data = table_data
-            .into_iter()
-            .filter(|elem| elem.id == id)
-            .collect();
-        Table::new(table_data)
-    } else {
-        Table::new(table_data)
+    if let Err(err) = q3::init_python() {
+        eprintln!("error: {err}");
+        return ExitCode::FAILURE;
+    }
+
+    if args.sandbox {
+        sandbox::enable(Sandbox {
+            allowed_modules: args.allowed_modules,
+        });
+    }
+
+    let command = std::env::current_exe().map(|program| WorkerCommand {
+        program,
+        args: vec!["worker".into()],
+    });
+
+    // Sandboxed scripts also run apart from the q3 process, and so do the scripts of parallel
+    // builds, which would otherwise wait for each other's Python lock
+    let all_scripts =
+        args.sandbox || matches!(&args.command, Command::Build(build_args) if build_args.jobs > 1);
+
+    match command {
+        Ok(command) if all_scripts => worker::enable(command),
+        Ok(command) => worker::register(command),
+        Err(err) if args.sandbox => {
+            eprintln!("error: sandboxed scripts can't run: {err}");
+            return ExitCode::FAILURE;
+        }
+        Err(err) => eprintln!("warning: scripts with limits can't run: {err}"),
+    }
+
+    if !args.no_cache {
+        q3::cache::enable(Cache::new(CACHE_DIR));
+    }
+
+    let result = match args.command {
+        Command::Build(build_args) => commands::build(build_args),
+        Command::Check(check_args) => commands::check(check_args),
+        Command::Get(get_args) => commands::get(get_args),
+        Command::Explain(explain_args) => commands::explain(explain_args),
+        Command::Ls(ls_args) => commands::ls(ls_args),
+        Command::Graph(graph_args) => commands::graph(graph_args),
+        Command::Diff(diff_args) => commands::diff(diff_args),
+        Command::Watch(watch_args) => commands::watch(watch_args),
+        Command::Run(run_args) => commands::run(run_args),
+        Command::Eval(eval_args) => commands::eval(eval_args),
+        Command::Count(count_args) => commands::count(count_args),
+        Command::ExplainHits(explain_hits_args) => commands::explain_hits(explain_hits_args),
+        Command::Report(report_args) => commands::report(report_args),
+        Command::Browse(browse_args) => commands::browse(browse_args),
+        Command::Repl(repl_args) => commands::repl(repl_args),
+        Command::Worker => q3::worker::serve().map_err(Into::into),
     };
 
-    let settings = Settings::new(
-        Width::increase(40).priority::<PriorityMin>(),
-        Width::wrap(Percent(70)).priority::<PriorityMax>(),
-    );
-
-    table
-        .with(settings)
-        .modify(Rows::new(1..), Padding::new(0, 0, 0, 1))
-        .with(Style::rounded());
-
-    println!("{}", table.to_string());
-
-    Ok(())
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use clap::ValueEnum;
+use serde_json::json;
+
+use crate::{Id, Q3Components, Q3Error, QStore};
+
+/// Name of the file listing what was written to the output directory
+pub const MANIFEST: &str = "manifest.json";
+
+/// Format of the files queries are written to
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// The expanded query, as is
+    Txt,
+    /// `{"id": <id>, "query": <expanded query>}`
+    Json,
+}
+
+impl OutputFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Txt => "txt",
+            Self::Json => "json",
+        }
+    }
+
+    fn render(&self, id: &Id, query: &str) -> String {
+        match self {
+            Self::Txt => query.to_string(),
+            Self::Json => json!({ "id": id.0, "query": query }).to_string(),
+        }
+    }
+}
+
+/// Path a query is written to, relative to the output directory.
+///
+/// Paths must stay inside the output directory, and ids used as file names can't hold a path
+/// separator.
+pub fn output_path(
+    id: &Id,
+    outputs: &HashMap<Id, PathBuf>,
+    format: OutputFormat,
+) -> Result<PathBuf, Q3Error> {
+    let invalid = |path: &Path, reason: &str| {
+        Q3Error::InvalidOutputPath(id.clone(), path.to_path_buf(), reason.into())
+    };
+
+    let path = match outputs.get(id) {
+        Some(path) => {
+            if !path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                return Err(invalid(
+                    path,
+                    "output paths must be relative, without `..` or `.` components",
+                ));
+            }
+
+            path.clone()
+        }
+        None => {
+            let path = PathBuf::from(format!("{id}.{}", format.extension()));
+
+            if id.0.chars().any(std::path::is_separator) {
+                return Err(invalid(
+                    &path,
+                    "ids holding a path separator need an output path",
+                ));
+            }
+
+            path
+        }
+    };
+
+    if path == Path::new(MANIFEST) {
+        return Err(invalid(&path, "the manifest is written there"));
+    }
+
+    Ok(path)
+}
+
+/// Writes every query of an expanded store to its own file in `dir`, followed by a manifest.
+///
+/// Queries are written to `<id>.<extension>` unless `outputs` holds a path for them. Returns the
+/// ids and paths of the written files relative to `dir`, sorted by id, as listed in the manifest.
+/// Nothing is written if a path is invalid or shared by two queries.
+pub fn write_queries(
+    store: &QStore,
+    outputs: &HashMap<Id, PathBuf>,
+    dir: &Path,
+    format: OutputFormat,
+) -> Result<Vec<(Id, PathBuf)>, Q3Error> {
+    let mut files: HashMap<PathBuf, (Id, String)> = HashMap::new();
+
+    for (id, component) in &store.components {
+        let Q3Components::Query(query) = component else {
+            continue;
+        };
+
+        let path = output_path(id, outputs, format)?;
+
+        if let Some((other, _)) = files.get(&path) {
+            return Err(Q3Error::InvalidOutputPath(
+                id.clone(),
+                path,
+                format!("{other} is written there too"),
+            ));
+        }
+
+        files.insert(path, (id.clone(), format.render(id, &query.to_string())));
+    }
+
+    let mut written: Vec<(Id, PathBuf)> = Vec::new();
+
+    for (path, (id, content)) in files {
+        let full_path = dir.join(&path);
+
+        if let Some(parent) = full_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(&full_path, content)?;
+        written.push((id, path));
+    }
+
+    written.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+
+    let manifest: Vec<serde_json::Value> = written
+        .iter()
+        .map(|(id, path)| json!({ "id": id.0, "path": path }))
+        .collect();
+
+    std::fs::write(
+        dir.join(MANIFEST),
+        serde_json::to_string_pretty(&json!({ "files": manifest }))
+            .map_err(std::io::Error::from)?,
+    )?;
+
+    Ok(written)
+}
+
+#[test]
+fn test_write_queries() {
+    use crate::Query;
+
+    let dir = std::env::temp_dir().join(format!("q3-output-{}", std::process::id()));
+
+    let mut store = QStore::new();
+    store.insert(Q3Components::Query(Query::new("q1", "lorem").unwrap()));
+    store.insert(Q3Components::Query(Query::new("q2", "ipsum").unwrap()));
+
+    let outputs = HashMap::from([(Id("q2".into()), PathBuf::from("nested/q2.query"))]);
+
+    let written = write_queries(&store, &outputs, &dir, OutputFormat::Json).unwrap();
+
+    assert_eq!(
+        written,
+        vec![
+            (Id("q1".into()), PathBuf::from("q1.json")),
+            (Id("q2".into()), PathBuf::from("nested/q2.query")),
+        ]
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("q1.json")).unwrap(),
+        r#"{"id":"q1","query":"lorem"}"#
+    );
+    assert!(std::fs::read_to_string(dir.join(MANIFEST))
+        .unwrap()
+        .contains("nested/q2.query"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_invalid_output_paths() {
+    use crate::Query;
+
+    let dir = std::env::temp_dir().join(format!("q3-invalid-output-{}", std::process::id()));
+
+    let write = |id: &str, output: Option<&str>, format: OutputFormat| {
+        let mut store = QStore::new();
+        store.insert(Q3Components::Query(Query::new(id, "lorem").unwrap()));
+        store.insert(Q3Components::Query(Query::new("other", "ipsum").unwrap()));
+
+        let outputs: HashMap<Id, PathBuf> = output
+            .map(|output| (Id(id.into()), PathBuf::from(output)))
+            .into_iter()
+            .collect();
+
+        write_queries(&store, &outputs, &dir, format)
+    };
+
+    for (id, output, format) in [
+        ("q1", Some("/tmp/q1.txt"), OutputFormat::Txt),
+        ("q1", Some("../q1.txt"), OutputFormat::Txt),
+        ("q1", Some("nested/../../q1.txt"), OutputFormat::Txt),
+        ("q/1", None, OutputFormat::Txt),
+        ("manifest", None, OutputFormat::Json),
+        ("q1", Some("manifest.json"), OutputFormat::Txt),
+        ("q1", Some("other.txt"), OutputFormat::Txt),
+    ] {
+        assert!(
+            matches!(
+                write(id, output, format),
+                Err(Q3Error::InvalidOutputPath(..))
+            ),
+            "{id} written to {output:?}"
+        );
+    }
+
+    assert!(!dir.exists());
+    assert!(write("q/1", Some("q1.txt"), OutputFormat::Txt).is_ok());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
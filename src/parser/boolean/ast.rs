@@ -0,0 +1,70 @@
+use std::fmt::Display;
+
+/// Ast of an expanded, Lucene-like, boolean query
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryAst {
+    /// A single term, wildcards included (`lorem`, `lor*m`)
+    Term(String),
+    /// A quoted phrase, stored without its quotes (`"lorem ipsum"`)
+    Phrase(String),
+    /// A range, stored verbatim with its brackets (`[2000 TO 2010]`)
+    Range(String),
+    /// A query restricted to a field (`title:lorem`, `title:(lorem OR ipsum)`)
+    Field { name: String, query: Box<QueryAst> },
+    /// Clauses joined with `AND`
+    And(Vec<QueryAst>),
+    /// Clauses joined with `OR`
+    Or(Vec<QueryAst>),
+    /// Clauses juxtaposed without operator, left to the default operator of the search engine
+    Sequence(Vec<QueryAst>),
+    /// A negated clause (`NOT lorem`, `-lorem`)
+    Not(Box<QueryAst>),
+    /// A required clause (`+lorem`)
+    Required(Box<QueryAst>),
+    /// A parenthesized clause
+    Group(Box<QueryAst>),
+    /// A proximity search on a phrase or a fuzzy search on a term (`"lorem ipsum"~5`, `lorem~`)
+    Proximity {
+        query: Box<QueryAst>,
+        distance: Option<u32>,
+    },
+    /// A boosted clause (`lorem^2.5`)
+    Boost { query: Box<QueryAst>, boost: String },
+}
+
+fn join(
+    f: &mut std::fmt::Formatter<'_>,
+    clauses: &[QueryAst],
+    separator: &str,
+) -> std::fmt::Result {
+    for (index, clause) in clauses.iter().enumerate() {
+        if index > 0 {
+            write!(f, "{}", separator)?;
+        }
+        write!(f, "{}", clause)?;
+    }
+
+    Ok(())
+}
+
+impl Display for QueryAst {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Term(term) => write!(f, "{}", term),
+            Self::Phrase(phrase) => write!(f, "\"{}\"", phrase),
+            Self::Range(range) => write!(f, "{}", range),
+            Self::Field { name, query } => write!(f, "{}:{}", name, query),
+            Self::And(clauses) => join(f, clauses, " AND "),
+            Self::Or(clauses) => join(f, clauses, " OR "),
+            Self::Sequence(clauses) => join(f, clauses, " "),
+            Self::Not(query) => write!(f, "NOT {}", query),
+            Self::Required(query) => write!(f, "+{}", query),
+            Self::Group(query) => write!(f, "({})", query),
+            Self::Proximity { query, distance } => match distance {
+                Some(distance) => write!(f, "{}~{}", query, distance),
+                None => write!(f, "{}~", query),
+            },
+            Self::Boost { query, boost } => write!(f, "{}^{}", query, boost),
+        }
+    }
+}
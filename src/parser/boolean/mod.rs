@@ -0,0 +1,239 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag},
+    character::complete::{anychar, char, digit1, multispace0, none_of, one_of, satisfy},
+    combinator::{all_consuming, map, not, opt, peek, recognize, verify},
+    multi::{many0, many1, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+
+mod ast;
+pub use ast::QueryAst;
+
+mod normalize;
+
+mod pretty;
+pub use pretty::PrettyPrinter;
+
+use crate::Q3Error;
+
+const KEYWORDS: [&str; 5] = ["AND", "OR", "NOT", "&&", "||"];
+
+fn is_term_char(c: char) -> bool {
+    !c.is_whitespace() && !"()\"[]{}:^~\\".contains(c)
+}
+
+fn ws<'a, O, P>(inner: P) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+where
+    P: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    delimited(multispace0, inner, multispace0)
+}
+
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(tag(word), not(peek(satisfy(is_term_char))))
+}
+
+fn term_text(input: &str) -> IResult<&str, &str> {
+    verify(
+        recognize(many1(alt((
+            recognize(preceded(char('\\'), anychar)),
+            recognize(satisfy(is_term_char)),
+        )))),
+        |term: &str| !KEYWORDS.contains(&term) && !term.starts_with(['+', '-', '!']),
+    )(input)
+}
+
+pub fn parse_term(input: &str) -> IResult<&str, QueryAst> {
+    map(term_text, |term: &str| QueryAst::Term(term.into()))(input)
+}
+
+pub fn parse_phrase(input: &str) -> IResult<&str, QueryAst> {
+    map(
+        delimited(
+            char('"'),
+            recognize(many0(alt((
+                recognize(preceded(char('\\'), anychar)),
+                recognize(none_of("\\\"")),
+            )))),
+            char('"'),
+        ),
+        |phrase: &str| QueryAst::Phrase(phrase.into()),
+    )(input)
+}
+
+pub fn parse_range(input: &str) -> IResult<&str, QueryAst> {
+    map(
+        recognize(tuple((one_of("[{"), is_not("]}"), one_of("]}")))),
+        |range: &str| QueryAst::Range(range.into()),
+    )(input)
+}
+
+pub fn parse_group(input: &str) -> IResult<&str, QueryAst> {
+    map(
+        delimited(char('('), ws(parse_sequence), char(')')),
+        |query| QueryAst::Group(Box::new(query)),
+    )(input)
+}
+
+pub fn parse_field(input: &str) -> IResult<&str, QueryAst> {
+    map(
+        pair(terminated(term_text, char(':')), parse_primary),
+        |(name, query)| QueryAst::Field {
+            name: name.into(),
+            query: Box::new(query),
+        },
+    )(input)
+}
+
+fn parse_primary(input: &str) -> IResult<&str, QueryAst> {
+    alt((
+        parse_group,
+        parse_field,
+        parse_phrase,
+        parse_range,
+        parse_term,
+    ))(input)
+}
+
+fn parse_modifiers(input: &str) -> IResult<&str, QueryAst> {
+    let (input, query) = parse_primary(input)?;
+    let (input, distance) = opt(preceded(char('~'), opt(digit1)))(input)?;
+    let (input, boost) = opt(preceded(
+        char('^'),
+        recognize(pair(digit1, opt(pair(char('.'), digit1)))),
+    ))(input)?;
+
+    let query = match distance {
+        Some(distance) => QueryAst::Proximity {
+            query: Box::new(query),
+            distance: distance.and_then(|distance| distance.parse().ok()),
+        },
+        None => query,
+    };
+
+    let query = match boost {
+        Some(boost) => QueryAst::Boost {
+            query: Box::new(query),
+            boost: boost.into(),
+        },
+        None => query,
+    };
+
+    Ok((input, query))
+}
+
+fn parse_unary(input: &str) -> IResult<&str, QueryAst> {
+    alt((
+        map(
+            preceded(
+                pair(alt((keyword("NOT"), tag("!"))), multispace0),
+                parse_unary,
+            ),
+            |query| QueryAst::Not(Box::new(query)),
+        ),
+        map(preceded(char('-'), parse_unary), |query| {
+            QueryAst::Not(Box::new(query))
+        }),
+        map(preceded(char('+'), parse_unary), |query| {
+            QueryAst::Required(Box::new(query))
+        }),
+        parse_modifiers,
+    ))(input)
+}
+
+fn parse_and(input: &str) -> IResult<&str, QueryAst> {
+    map(
+        separated_list1(ws(alt((keyword("AND"), tag("&&")))), parse_unary),
+        |mut clauses| match clauses.len() {
+            1 => clauses.remove(0),
+            _ => QueryAst::And(clauses),
+        },
+    )(input)
+}
+
+fn parse_or(input: &str) -> IResult<&str, QueryAst> {
+    map(
+        separated_list1(ws(alt((keyword("OR"), tag("||")))), parse_and),
+        |mut clauses| match clauses.len() {
+            1 => clauses.remove(0),
+            _ => QueryAst::Or(clauses),
+        },
+    )(input)
+}
+
+fn parse_sequence(input: &str) -> IResult<&str, QueryAst> {
+    map(many1(ws(parse_or)), |mut clauses| match clauses.len() {
+        1 => clauses.remove(0),
+        _ => QueryAst::Sequence(clauses),
+    })(input)
+}
+
+/// Parses an expanded query using a Lucene-like boolean grammar
+pub fn parse_boolean_query(input: &str) -> Result<QueryAst, Q3Error> {
+    let (_rest, query) = all_consuming(ws(parse_sequence))(input).map_err(|err| match err {
+        nom::Err::Error(err) | nom::Err::Failure(err) => {
+            Q3Error::FailedToParseBooleanQuery(err.input.chars().take(30).collect())
+        }
+        nom::Err::Incomplete(_) => Q3Error::FailedToParseBooleanQuery(String::new()),
+    })?;
+
+    Ok(query)
+}
+
+#[test]
+fn test_parse_boolean_query() {
+    let query = parse_boolean_query(r#"title:("lorem ipsum" OR dolor*) AND NOT -sit"#).unwrap();
+
+    assert_eq!(
+        query,
+        QueryAst::And(vec![
+            QueryAst::Field {
+                name: "title".into(),
+                query: Box::new(QueryAst::Group(Box::new(QueryAst::Or(vec![
+                    QueryAst::Phrase("lorem ipsum".into()),
+                    QueryAst::Term("dolor*".into()),
+                ])))),
+            },
+            QueryAst::Not(Box::new(QueryAst::Not(Box::new(QueryAst::Term(
+                "sit".into()
+            ))))),
+        ])
+    );
+}
+
+#[test]
+fn test_parse_boolean_query_modifiers() {
+    let query = parse_boolean_query(r#"ANDROID "lorem ipsum"~5^2 year:[2000 TO 2010]"#).unwrap();
+
+    assert_eq!(
+        query,
+        QueryAst::Sequence(vec![
+            QueryAst::Term("ANDROID".into()),
+            QueryAst::Boost {
+                query: Box::new(QueryAst::Proximity {
+                    query: Box::new(QueryAst::Phrase("lorem ipsum".into())),
+                    distance: Some(5),
+                }),
+                boost: "2".into(),
+            },
+            QueryAst::Field {
+                name: "year".into(),
+                query: Box::new(QueryAst::Range("[2000 TO 2010]".into())),
+            },
+        ])
+    );
+
+    assert_eq!(
+        query.to_string(),
+        r#"ANDROID "lorem ipsum"~5^2 year:[2000 TO 2010]"#
+    );
+}
+
+#[test]
+fn test_parse_boolean_query_errors() {
+    assert!(parse_boolean_query("title:(lorem OR ipsum").is_err());
+    assert!(parse_boolean_query("lorem AND").is_err());
+    assert!(parse_boolean_query("\"lorem").is_err());
+}
@@ -0,0 +1,154 @@
+use super::QueryAst;
+
+impl QueryAst {
+    /// Removes redundant parentheses and duplicate clauses.
+    ///
+    /// Parentheses are only removed where it can't change the meaning of the query: around a
+    /// single clause, around a group, around the whole query, and around a clause nested in an
+    /// operator of the same kind (`a OR (b OR c)`).
+    pub fn normalize(self) -> Self {
+        match self.normalize_node() {
+            Self::Group(query) => *query,
+            query => query,
+        }
+    }
+
+    fn normalize_node(self) -> Self {
+        match self {
+            Self::Group(query) => match query.normalize_node() {
+                query @ (Self::Term(_)
+                | Self::Phrase(_)
+                | Self::Range(_)
+                | Self::Group(_)
+                | Self::Field { .. }
+                | Self::Proximity { .. }
+                | Self::Boost { .. }) => query,
+                query => Self::Group(Box::new(query)),
+            },
+            Self::And(clauses) => Self::flatten(clauses, Self::And, |clause| match clause {
+                Self::And(clauses) => Ok(clauses),
+                clause => Err(clause),
+            }),
+            Self::Or(clauses) => Self::flatten(clauses, Self::Or, |clause| match clause {
+                Self::Or(clauses) => Ok(clauses),
+                clause => Err(clause),
+            }),
+            // Prohibited and required clauses change what the rest of a sequence means, so
+            // `a (b -c)` and `+a (b c)` keep their parentheses
+            Self::Sequence(clauses) => match clauses.iter().all(Self::is_optional) {
+                true => Self::flatten(clauses, Self::Sequence, |clause| match clause {
+                    Self::Sequence(clauses) if clauses.iter().all(Self::is_optional) => Ok(clauses),
+                    clause => Err(clause),
+                }),
+                false => Self::flatten(clauses, Self::Sequence, Err),
+            },
+            Self::Field { name, query } => Self::Field {
+                name,
+                query: Box::new(query.normalize_node()),
+            },
+            Self::Not(query) => Self::Not(Box::new(query.normalize_node())),
+            Self::Required(query) => Self::Required(Box::new(query.normalize_node())),
+            Self::Proximity { query, distance } => Self::Proximity {
+                query: Self::normalize_modified(*query, false),
+                distance,
+            },
+            Self::Boost { query, boost } => Self::Boost {
+                query: Self::normalize_modified(*query, true),
+                boost,
+            },
+            leaf => leaf,
+        }
+    }
+
+    /// Normalizes the clause of a proximity or a boost, which keeps its parentheses if it has a
+    /// modifier that can't be written before the one of its parent: `(a^2)^3` or `(a~2)~3`, but
+    /// `a~2^3`
+    fn normalize_modified(query: QueryAst, parent_is_boost: bool) -> Box<QueryAst> {
+        match query.normalize_node() {
+            query @ Self::Boost { .. } => Box::new(Self::Group(Box::new(query))),
+            query @ Self::Proximity { .. } if !parent_is_boost => {
+                Box::new(Self::Group(Box::new(query)))
+            }
+            query => Box::new(query),
+        }
+    }
+
+    /// Whether a clause of a sequence is neither prohibited nor required
+    fn is_optional(clause: &QueryAst) -> bool {
+        !matches!(clause, Self::Not(_) | Self::Required(_))
+    }
+
+    /// Normalizes the clauses of an operator, inlines the clauses of nested operators of the same
+    /// kind and removes duplicated clauses
+    fn flatten(
+        clauses: Vec<QueryAst>,
+        operator: fn(Vec<QueryAst>) -> QueryAst,
+        same_operator: fn(QueryAst) -> Result<Vec<QueryAst>, QueryAst>,
+    ) -> Self {
+        let mut result: Vec<QueryAst> = Vec::with_capacity(clauses.len());
+
+        for clause in clauses {
+            let clause = match clause.normalize_node() {
+                Self::Group(query) => {
+                    same_operator(*query).map_err(|query| Self::Group(Box::new(query)))
+                }
+                clause => same_operator(clause),
+            };
+
+            let clauses = match clause {
+                Ok(clauses) => clauses,
+                Err(clause) => vec![clause],
+            };
+
+            for clause in clauses {
+                if !result.contains(&clause) {
+                    result.push(clause);
+                }
+            }
+        }
+
+        match result.len() {
+            1 => result.remove(0),
+            _ => operator(result),
+        }
+    }
+}
+
+#[test]
+fn test_normalize() {
+    use super::parse_boolean_query;
+
+    let cases = [
+        ("((lorem))", "lorem"),
+        ("(lorem OR ipsum)", "lorem OR ipsum"),
+        (
+            "lorem OR (ipsum OR (dolor OR lorem))",
+            "lorem OR ipsum OR dolor",
+        ),
+        ("lorem AND (ipsum OR dolor)", "lorem AND (ipsum OR dolor)"),
+        ("title:((lorem)) AND title:lorem", "title:lorem"),
+        (
+            "title:(lorem OR lorem OR (ipsum))",
+            "title:(lorem OR ipsum)",
+        ),
+        (
+            "NOT (lorem OR ipsum) (\"a b\")~2",
+            "NOT (lorem OR ipsum) \"a b\"~2",
+        ),
+        ("a (b c)", "a b c"),
+        ("a (b -c)", "a (b NOT c)"),
+        ("+a (b c)", "+a (b c)"),
+        ("(a^2)^3", "(a^2)^3"),
+        ("(a~2)~3", "(a~2)~3"),
+        ("((a~2))^3", "a~2^3"),
+        ("(a^2)~3", "(a^2)~3"),
+        ("(\"a b\")~2^3", "\"a b\"~2^3"),
+    ];
+
+    for (input, expected) in cases {
+        let normalized = parse_boolean_query(input).unwrap().normalize().to_string();
+
+        assert_eq!(normalized, expected);
+        assert!(parse_boolean_query(&normalized).is_ok(), "{normalized}");
+    }
+}
@@ -0,0 +1,123 @@
+use super::QueryAst;
+
+/// Prints a [`QueryAst`] on several lines, indenting nested groups.
+///
+/// A clause is kept on a single line as long as it fits in `width` columns.
+#[derive(Debug, Clone)]
+pub struct PrettyPrinter {
+    pub indent: usize,
+    pub width: usize,
+}
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            width: 80,
+        }
+    }
+}
+
+impl PrettyPrinter {
+    pub fn print(&self, query: &QueryAst) -> String {
+        self.print_node(query, 0)
+    }
+
+    fn print_node(&self, query: &QueryAst, level: usize) -> String {
+        let flat = query.to_string();
+
+        if level * self.indent + flat.chars().count() <= self.width {
+            return flat;
+        }
+
+        let newline = format!("\n{}", " ".repeat(level * self.indent));
+
+        match query {
+            QueryAst::And(clauses) => self.print_clauses(clauses, level, &format!("{newline}AND ")),
+            QueryAst::Or(clauses) => self.print_clauses(clauses, level, &format!("{newline}OR ")),
+            QueryAst::Sequence(clauses) => self.print_sequence(clauses, level, &newline),
+            QueryAst::Group(query) => format!(
+                "(\n{}{}{newline})",
+                " ".repeat((level + 1) * self.indent),
+                self.print_node(query, level + 1)
+            ),
+            QueryAst::Field { name, query } => {
+                format!("{}:{}", name, self.print_node(query, level))
+            }
+            QueryAst::Not(query) => format!("NOT {}", self.print_node(query, level)),
+            QueryAst::Required(query) => format!("+{}", self.print_node(query, level)),
+            QueryAst::Proximity { query, distance } => format!(
+                "{}~{}",
+                self.print_node(query, level),
+                distance
+                    .map(|distance| distance.to_string())
+                    .unwrap_or_default()
+            ),
+            QueryAst::Boost { query, boost } => {
+                format!("{}^{}", self.print_node(query, level), boost)
+            }
+            _ => flat,
+        }
+    }
+
+    fn print_clauses(&self, clauses: &[QueryAst], level: usize, separator: &str) -> String {
+        clauses
+            .iter()
+            .map(|clause| self.print_node(clause, level))
+            .collect::<Vec<String>>()
+            .join(separator)
+    }
+
+    /// Juxtaposed single line clauses are packed on the same line as long as they fit
+    fn print_sequence(&self, clauses: &[QueryAst], level: usize, newline: &str) -> String {
+        let mut result = String::new();
+        let mut line_width: Option<usize> = None;
+
+        for clause in clauses {
+            let clause = self.print_node(clause, level);
+            let clause_width = clause.chars().count();
+
+            match line_width {
+                Some(width) if !clause.contains('\n') && width + 1 + clause_width <= self.width => {
+                    result.push(' ');
+                    line_width = Some(width + 1 + clause_width);
+                }
+                _ => {
+                    if !result.is_empty() {
+                        result.push_str(newline);
+                    }
+                    line_width = match clause.contains('\n') {
+                        true => None,
+                        false => Some(level * self.indent + clause_width),
+                    };
+                }
+            }
+
+            result.push_str(&clause);
+        }
+
+        result
+    }
+}
+
+#[test]
+fn test_pretty_print() {
+    use super::parse_boolean_query;
+
+    let query = parse_boolean_query(
+        "title:(lorem OR ipsum OR dolor) AND (sit OR amet) NOT consectetur a b",
+    )
+    .unwrap();
+
+    let printer = PrettyPrinter {
+        indent: 2,
+        width: 20,
+    };
+
+    assert_eq!(
+        printer.print(&query),
+        "title:(\n  lorem\n  OR ipsum\n  OR dolor\n)\nAND (sit OR amet)\nNOT consectetur a b"
+    );
+
+    assert_eq!(PrettyPrinter::default().print(&query), query.to_string());
+}
@@ -10,6 +10,12 @@ use nom::{
 mod ast;
 pub use ast::Q3Ast;
 
+mod boolean;
+pub use boolean::{parse_boolean_query, PrettyPrinter, QueryAst};
+
+mod template;
+pub use template::{parse_template, TemplateToken};
+
 use crate::Q3Error;
 
 use nom::branch::alt;
@@ -34,7 +40,7 @@ pub fn parse_any(input: &str) -> IResult<&str, Q3Ast> {
     )(input)
 }
 
-pub fn parse_query<'a>(input: &'a str) -> Result<Vec<Q3Ast>, Q3Error> {
+pub fn parse_query(input: &str) -> Result<Vec<Q3Ast>, Q3Error> {
     let (_rest, matched) = all_consuming(many_till(alt((parse_id, parse_any)), eof))(input)
         .map_err(|_err| Q3Error::FailedToParseQuery)?;
     Ok(matched.0)
@@ -0,0 +1,87 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag},
+    character::complete::{anychar, multispace0},
+    combinator::{all_consuming, eof, map, opt, peek, recognize},
+    multi::{many_till, separated_list1},
+    sequence::{delimited, pair, preceded},
+    IResult,
+};
+
+use crate::Q3Error;
+
+/// Token of an ad-hoc template, as typed in the REPL
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateToken {
+    /// A reference of type `#{id}` or `#{id | transform | transform}`
+    Reference { id: String, transforms: Vec<String> },
+    /// Anything except a reference
+    Text(String),
+}
+
+fn name(input: &str) -> IResult<&str, &str> {
+    delimited(multispace0, is_not("{}| \t"), multispace0)(input)
+}
+
+fn parse_reference(input: &str) -> IResult<&str, TemplateToken> {
+    map(
+        delimited(
+            tag("#{"),
+            pair(
+                name,
+                opt(preceded(tag("|"), separated_list1(tag("|"), name))),
+            ),
+            tag("}"),
+        ),
+        |(id, transforms): (&str, Option<Vec<&str>>)| TemplateToken::Reference {
+            id: id.into(),
+            transforms: transforms
+                .unwrap_or_default()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        },
+    )(input)
+}
+
+fn parse_text(input: &str) -> IResult<&str, TemplateToken> {
+    map(
+        many_till(
+            anychar,
+            alt((recognize(peek(parse_reference)), recognize(peek(eof)))),
+        ),
+        |elem| TemplateToken::Text(elem.0.into_iter().collect()),
+    )(input)
+}
+
+/// Parses a template, where references may pipe the component through transforms
+pub fn parse_template(input: &str) -> Result<Vec<TemplateToken>, Q3Error> {
+    let (_rest, matched) = all_consuming(many_till(alt((parse_reference, parse_text)), eof))(input)
+        .map_err(|_err| Q3Error::FailedToParseQuery)?;
+
+    Ok(matched.0)
+}
+
+#[test]
+fn test_parse_template() {
+    assert_eq!(
+        parse_template("title:(#{lorem | quote | join_or}) #{e}").unwrap(),
+        vec![
+            TemplateToken::Text("title:(".into()),
+            TemplateToken::Reference {
+                id: "lorem".into(),
+                transforms: vec!["quote".into(), "join_or".into()],
+            },
+            TemplateToken::Text(") ".into()),
+            TemplateToken::Reference {
+                id: "e".into(),
+                transforms: vec![],
+            },
+        ]
+    );
+
+    assert_eq!(
+        parse_template("#{lorem | } sit").unwrap(),
+        vec![TemplateToken::Text("#{lorem | } sit".into())]
+    );
+}
@@ -0,0 +1,326 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_until},
+    character::complete::{anychar, char},
+    combinator::{all_consuming, eof, map, opt, peek, recognize},
+    multi::many_till,
+    sequence::{delimited, pair, terminated},
+    IResult,
+};
+use serde_json::{json, Value};
+
+use crate::{Identify, Q3Components, Q3Error, QStore};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    /// `{{ path | filter }}`
+    Expression(&'a str),
+    /// `{% for item in path %}` or `{% endfor %}`
+    Block(&'a str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Expression {
+        path: Vec<String>,
+        filters: Vec<String>,
+    },
+    For {
+        variable: String,
+        path: Vec<String>,
+        body: Vec<Node>,
+    },
+}
+
+/// A template rendering the queries of a store, with `{{ query.id | filter }}` expressions and
+/// `{% for query in queries %}...{% endfor %}` loops.
+///
+/// Filters: `json` (quoted JSON string), `shell` (single quoted shell word), `url` (percent
+/// encoded), `upper` and `lower`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+fn parse_expression(input: &str) -> IResult<&str, Token<'_>> {
+    map(
+        delimited(tag("{{"), take_until("}}"), tag("}}")),
+        Token::Expression,
+    )(input)
+}
+
+/// A block swallows the line break following it, so blocks can sit on their own line
+fn parse_block(input: &str) -> IResult<&str, Token<'_>> {
+    map(
+        terminated(
+            delimited(tag("{%"), take_until("%}"), tag("%}")),
+            opt(char('\n')),
+        ),
+        Token::Block,
+    )(input)
+}
+
+fn parse_text(input: &str) -> IResult<&str, Token<'_>> {
+    map(
+        recognize(pair(
+            anychar,
+            many_till(
+                anychar,
+                peek(alt((recognize(tag("{{")), recognize(tag("{%")), eof))),
+            ),
+        )),
+        Token::Text,
+    )(input)
+}
+
+fn parse_path(input: &str) -> Result<Vec<String>, Q3Error> {
+    let path: Vec<String> = input.trim().split('.').map(String::from).collect();
+
+    match path
+        .iter()
+        .all(|key| !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_'))
+    {
+        true => Ok(path),
+        false => Err(Q3Error::InvalidTemplate(format!(
+            "invalid variable `{input}`"
+        ))),
+    }
+}
+
+fn build<'a>(
+    tokens: &mut impl Iterator<Item = Token<'a>>,
+    in_loop: bool,
+) -> Result<Vec<Node>, Q3Error> {
+    let mut nodes: Vec<Node> = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.into())),
+            Token::Expression(expression) => {
+                let mut parts = expression.split('|');
+                let path = parse_path(parts.next().unwrap_or_default())?;
+
+                nodes.push(Node::Expression {
+                    path,
+                    filters: parts.map(|filter| filter.trim().to_string()).collect(),
+                });
+            }
+            Token::Block(block) => match block.split_whitespace().collect::<Vec<&str>>()[..] {
+                ["for", variable, "in", path] => nodes.push(Node::For {
+                    variable: variable.into(),
+                    path: parse_path(path)?,
+                    body: build(tokens, true)?,
+                }),
+                ["endfor"] if in_loop => return Ok(nodes),
+                _ => {
+                    return Err(Q3Error::InvalidTemplate(format!(
+                        "unexpected block `{}`",
+                        block.trim()
+                    )))
+                }
+            },
+        }
+    }
+
+    match in_loop {
+        true => Err(Q3Error::InvalidTemplate("missing `{% endfor %}`".into())),
+        false => Ok(nodes),
+    }
+}
+
+impl Template {
+    pub fn parse(input: &str) -> Result<Self, Q3Error> {
+        let (_rest, (tokens, _eof)) = all_consuming(many_till(
+            alt((parse_expression, parse_block, parse_text)),
+            eof,
+        ))(input)
+        .map_err(|err| Q3Error::InvalidTemplate(err.to_string()))?;
+
+        Ok(Self {
+            nodes: build(&mut tokens.into_iter(), false)?,
+        })
+    }
+
+    /// Renders the template with the variables of `context`
+    pub fn render(&self, context: &Value) -> Result<String, Q3Error> {
+        let mut output = String::new();
+        render_nodes(&self.nodes, context, &mut Vec::new(), &mut output)?;
+
+        Ok(output)
+    }
+}
+
+fn lookup<'a>(
+    path: &[String],
+    context: &'a Value,
+    scope: &[(String, &'a Value)],
+) -> Result<&'a Value, Q3Error> {
+    let unknown = || Q3Error::InvalidTemplate(format!("unknown variable `{}`", path.join(".")));
+
+    let root = match scope.iter().rev().find(|(name, _)| *name == path[0]) {
+        Some((_, value)) => *value,
+        None => context.get(&path[0]).ok_or_else(unknown)?,
+    };
+
+    path[1..]
+        .iter()
+        .try_fold(root, |value, key| value.get(key).ok_or_else(unknown))
+}
+
+fn render_nodes<'a>(
+    nodes: &[Node],
+    context: &'a Value,
+    scope: &mut Vec<(String, &'a Value)>,
+    output: &mut String,
+) -> Result<(), Q3Error> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Expression { path, filters } => {
+                let mut text = to_text(lookup(path, context, scope)?);
+
+                for filter in filters {
+                    text = apply_filter(filter, &text)?;
+                }
+
+                output.push_str(&text);
+            }
+            Node::For {
+                variable,
+                path,
+                body,
+            } => {
+                let items = match lookup(path, context, scope)? {
+                    Value::Array(items) => items,
+                    _ => {
+                        return Err(Q3Error::InvalidTemplate(format!(
+                            "`{}` is not a list",
+                            path.join(".")
+                        )))
+                    }
+                };
+
+                for item in items {
+                    scope.push((variable.clone(), item));
+                    render_nodes(body, context, scope, output)?;
+                    scope.pop();
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(to_text)
+            .collect::<Vec<String>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn apply_filter(filter: &str, text: &str) -> Result<String, Q3Error> {
+    Ok(match filter {
+        "json" => Value::String(text.into()).to_string(),
+        "shell" => format!("'{}'", text.replace('\'', r"'\''")),
+        "url" => text
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (byte as char).to_string()
+                }
+                _ => format!("%{byte:02X}"),
+            })
+            .collect(),
+        "upper" => text.to_uppercase(),
+        "lower" => text.to_lowercase(),
+        _ => {
+            return Err(Q3Error::InvalidTemplate(format!(
+                "unknown filter `{filter}`"
+            )))
+        }
+    })
+}
+
+/// Variables available to templates: `queries`, a list of `{id, query, template, dependencies,
+/// description, tags, owner, status}` sorted by id, where `raw` is the store before expansion and
+/// `expanded` the store after it
+pub fn context(raw: &QStore, expanded: &QStore) -> Value {
+    let mut queries: Vec<(&Q3Components, &Q3Components)> = expanded
+        .components
+        .values()
+        .filter(|component| matches!(component, Q3Components::Query(_)))
+        .filter_map(|component| Some((raw.components.get(component.get_id())?, component)))
+        .collect();
+    queries.sort_by(|a, b| a.1.get_id().0.cmp(&b.1.get_id().0));
+
+    let queries: Vec<Value> = queries
+        .into_iter()
+        .map(|(raw, expanded)| {
+            json!({
+                "id": expanded.get_id().0,
+                "query": expanded.to_string(),
+                "template": raw.to_string(),
+                "dependencies": raw
+                    .dependencies()
+                    .into_iter()
+                    .map(|id| id.0)
+                    .collect::<Vec<String>>(),
+                "description": expanded.metadata().description,
+                "tags": expanded.metadata().tags,
+                "owner": expanded.metadata().owner,
+                "status": expanded.metadata().status.to_string(),
+            })
+        })
+        .collect();
+
+    json!({ "queries": queries })
+}
+
+#[test]
+fn test_render_template() {
+    use crate::Query;
+
+    let mut raw = QStore::new();
+    raw.insert(Q3Components::Query(Query::new("q1", "it's").unwrap()));
+    raw.insert(Q3Components::Query(
+        Query::new("q2", "#{q1} lorem").unwrap(),
+    ));
+
+    let mut expanded = raw.clone();
+    expanded.expand_all();
+
+    let template = Template::parse(
+        "{% for query in queries %}\n\
+         curl -d {{ query.query | shell }} # {{ query.id | upper }} <- {{ query.dependencies }}\n\
+         {% endfor %}",
+    )
+    .unwrap();
+
+    assert_eq!(
+        template.render(&context(&raw, &expanded)).unwrap(),
+        "curl -d 'it'\\''s' # Q1 <- \n\
+         curl -d 'it'\\''s lorem' # Q2 <- q1\n"
+    );
+
+    let template = Template::parse("q={{ text | url }} {{ text | json }}").unwrap();
+
+    assert_eq!(
+        template.render(&json!({ "text": "a \"b\"" })).unwrap(),
+        r#"q=a%20%22b%22 "a \"b\"""#
+    );
+
+    assert!(Template::parse("{% for query in queries %}").is_err());
+    assert!(Template::parse("{{ query.id }}")
+        .unwrap()
+        .render(&context(&raw, &expanded))
+        .is_err());
+}
@@ -0,0 +1,277 @@
+use std::io::{BufRead, Write};
+
+use crate::parser::{parse_template, TemplateToken};
+use crate::script::{join_and, join_or, normalize_spaces, quote, trim, uniq};
+use crate::{
+    Id, Identify, Limits, List, Metadata, Q3Components, Q3Error, QStore, Query, ScriptLang,
+};
+
+const HELP: &str = "\
+<template>                expand a template, e.g. title:(#{lorem | quote | join_or})
+:let <id> = <template>    define a temporary query
+:list <id> = <a>, <b>     define a temporary list
+:items <id>               show the items of a list and the output of its script
+:ls                       list components
+:help                     show this help
+:quit                     leave the REPL
+
+Transforms: quote, trim, normalize_spaces, uniq, join_or, join_and";
+
+/// Value of a reference while it goes through its transforms
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Items(Vec<String>),
+    Text(String),
+}
+
+impl Value {
+    fn into_items(self) -> Vec<String> {
+        match self {
+            Self::Items(items) => items,
+            Self::Text(text) => vec![text],
+        }
+    }
+
+    fn transform(self, name: &str) -> Result<Self, Q3Error> {
+        Ok(match name {
+            "quote" => Self::Items(quote(self.into_items())),
+            "trim" => Self::Items(trim(self.into_items())),
+            "normalize_spaces" => Self::Items(normalize_spaces(self.into_items())),
+            "uniq" => Self::Items(uniq(self.into_items())),
+            "join_or" => Self::Text(join_or(self.into_items())),
+            "join_and" => Self::Text(join_and(self.into_items())),
+            _ => return Err(Q3Error::UnknownTransform(name.into())),
+        })
+    }
+}
+
+/// Interactive session on an expanded store
+#[derive(Debug)]
+pub struct Repl {
+    store: QStore,
+    /// Components that failed to expand when the q3 file was loaded
+    errors: Vec<(Id, Q3Error)>,
+}
+
+impl Repl {
+    pub fn new(store: QStore, errors: Vec<(Id, Q3Error)>) -> Self {
+        Self { store, errors }
+    }
+
+    /// Evaluates a line typed by the user, returning the text to print or `None` to leave
+    pub fn eval(&mut self, line: &str) -> Result<Option<String>, Q3Error> {
+        let line = line.trim();
+
+        if !line.starts_with(':') {
+            return self.expand(line).map(Some);
+        }
+
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        let output = match command {
+            ":q" | ":quit" => return Ok(None),
+            ":help" => HELP.to_string(),
+            ":ls" => self.ls(),
+            ":items" => self.items(rest)?,
+            ":let" => {
+                let (id, template) =
+                    definition(rest).ok_or_else(|| Q3Error::UnknownReplCommand(line.into()))?;
+                let query = Query::new(id.to_string(), self.expand(template)?)?;
+                let output = format!("{id} = {query}");
+
+                self.define(Q3Components::Query(query));
+                output
+            }
+            ":list" => {
+                let (id, value) =
+                    definition(rest).ok_or_else(|| Q3Error::UnknownReplCommand(line.into()))?;
+                let items: Vec<&str> = value.split(',').map(str::trim).collect();
+                let list = List {
+                    id: Id(id.into()),
+                    value: items.join(","),
+                    separator: ",".into(),
+                    script: None,
+                    script_lang: ScriptLang::default(),
+                    output: None,
+                    limits: Limits::default(),
+                    metadata: Metadata::default(),
+                };
+                let output = format!("{id} = {}", list.items().join(" | "));
+
+                self.define(Q3Components::List(list));
+                output
+            }
+            _ => return Err(Q3Error::UnknownReplCommand(command.into())),
+        };
+
+        Ok(Some(output))
+    }
+
+    /// Reads lines from `input` until it ends or the user quits
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> std::io::Result<()> {
+        let mut lines = input.lines();
+
+        loop {
+            write!(output, "q3> ")?;
+            output.flush()?;
+
+            let Some(line) = lines.next() else {
+                break;
+            };
+
+            match self.eval(&line?) {
+                Ok(Some(text)) if text.is_empty() => (),
+                Ok(Some(text)) => writeln!(output, "{text}")?,
+                Ok(None) => break,
+                Err(err) => eprintln!("error: {err}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn define(&mut self, component: Q3Components) {
+        self.errors.retain(|(id, _)| id != component.get_id());
+        self.store.insert(component);
+    }
+
+    fn expand(&self, template: &str) -> Result<String, Q3Error> {
+        parse_template(template)?
+            .into_iter()
+            .map(|token| match token {
+                TemplateToken::Text(text) => Ok(text),
+                TemplateToken::Reference { id, transforms } => self.resolve(&id, &transforms),
+            })
+            .collect()
+    }
+
+    fn resolve(&self, id: &str, transforms: &[String]) -> Result<String, Q3Error> {
+        if self.failure(id).is_some() {
+            return Err(Q3Error::DependencyFailed(id.into()));
+        }
+
+        let component = self
+            .store
+            .get(id)
+            .ok_or_else(|| Q3Error::IdNotFound(id.into()))?;
+
+        if transforms.is_empty() {
+            return Ok(component.to_string());
+        }
+
+        let mut value = match &component {
+            Q3Components::List(list) => {
+                Value::Items(list.items().into_iter().map(String::from).collect())
+            }
+            _ => Value::Text(component.to_string()),
+        };
+
+        for transform in transforms {
+            value = value.transform(transform)?;
+        }
+
+        Ok(match value {
+            Value::Items(items) => items.join(" "),
+            Value::Text(text) => text,
+        })
+    }
+
+    fn failure(&self, id: &str) -> Option<&Q3Error> {
+        self.errors
+            .iter()
+            .find(|(failed_id, _)| failed_id.0 == id)
+            .map(|(_, err)| err)
+    }
+
+    fn ls(&self) -> String {
+        let mut components: Vec<&Q3Components> = self.store.components.values().collect();
+        components.sort_by(|a, b| a.get_id().0.cmp(&b.get_id().0));
+
+        components
+            .into_iter()
+            .map(|component| {
+                let id = component.get_id();
+
+                match self.failure(&id.0) {
+                    Some(err) => format!("{id} ({}) failed: {err}", component.kind()),
+                    None => format!("{id} ({})", component.kind()),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn items(&self, id: &str) -> Result<String, Q3Error> {
+        let list = match self.store.get(id) {
+            Some(Q3Components::List(list)) => list,
+            Some(_) => return Err(Q3Error::NotAList(id.into())),
+            None => return Err(Q3Error::IdNotFound(id.into())),
+        };
+
+        let mut lines: Vec<String> = list
+            .items()
+            .iter()
+            .enumerate()
+            .map(|(index, item)| format!("{:>3}  {item}", index + 1))
+            .collect();
+
+        if let Some(output) = &list.output {
+            lines.push(format!("=> {output}"));
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Splits `<id> = <value>`
+fn definition(input: &str) -> Option<(&str, &str)> {
+    let (id, value) = input.split_once('=')?;
+    let id = id.trim();
+
+    match id.is_empty() || id.contains(char::is_whitespace) {
+        true => None,
+        false => Some((id, value.trim())),
+    }
+}
+
+#[test]
+fn test_repl_eval() {
+    let mut store = QStore::new();
+    store.insert(Q3Components::Query(Query::new("title", "lorem").unwrap()));
+    store.insert(Q3Components::Query(
+        Query::new("broken", "#{missing}").unwrap(),
+    ));
+    let errors = store.expand_all();
+
+    let mut repl = Repl::new(store, errors);
+
+    assert_eq!(
+        repl.eval(":list words = lorem, lorem, ipsum").unwrap(),
+        Some("words = lorem | lorem | ipsum".into())
+    );
+    assert_eq!(
+        repl.eval("title:(#{words | uniq | quote | join_or}) #{title}")
+            .unwrap(),
+        Some("title:(\"lorem\" OR \"ipsum\") lorem".into())
+    );
+    assert_eq!(
+        repl.eval(":let q = #{title | quote}").unwrap(),
+        Some("q = \"lorem\"".into())
+    );
+    assert_eq!(repl.eval("#{q} sit").unwrap(), Some("\"lorem\" sit".into()));
+    assert_eq!(
+        repl.eval(":items words").unwrap(),
+        Some("  1  lorem\n  2  lorem\n  3  ipsum".into())
+    );
+
+    assert!(matches!(
+        repl.eval("#{title | reverse}"),
+        Err(Q3Error::UnknownTransform(_))
+    ));
+    assert!(matches!(
+        repl.eval("#{broken}"),
+        Err(Q3Error::DependencyFailed(_))
+    ));
+    assert_eq!(repl.eval(":q").unwrap(), None);
+}
@@ -0,0 +1,349 @@
+use clap::ValueEnum;
+
+use crate::parser::{parse_boolean_query, PrettyPrinter};
+use crate::{Id, Identify, Metadata, Q3Components, Q3Error, QStore};
+
+/// Format of the report of a q3 file
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    Md,
+    Html,
+}
+
+/// A list or generator used by a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    pub id: Id,
+    pub kind: &'static str,
+    /// Items of a list, empty for generators
+    pub items: Vec<String>,
+    pub value: String,
+}
+
+/// Everything the report says about a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryReport {
+    pub id: Id,
+    pub metadata: Metadata,
+    /// Expanded query, or the cause of its failure
+    pub query: Result<String, String>,
+    /// Pretty printed query, if it is a valid boolean query
+    pub structure: Option<String>,
+    /// Lists and generators used by the query, directly or through nested queries
+    pub sources: Vec<Source>,
+}
+
+/// Adds the lists and generators `id` is built from to `sources`, in order of appearance, taking
+/// their items from the raw store and their values from the expanded one
+fn collect_sources(
+    raw: &QStore,
+    expanded: &QStore,
+    id: &Id,
+    sources: &mut Vec<Source>,
+    visited: &mut Vec<Id>,
+) {
+    if visited.contains(id) {
+        return;
+    }
+    visited.push(id.clone());
+
+    match raw.components.get(id) {
+        Some(Q3Components::Query(query)) => {
+            for dependency in query.dependencies() {
+                collect_sources(raw, expanded, &dependency, sources, visited);
+            }
+        }
+        Some(component) => sources.push(Source {
+            id: id.clone(),
+            kind: component.kind(),
+            items: match component {
+                Q3Components::List(list) => list.items().into_iter().map(String::from).collect(),
+                _ => Vec::new(),
+            },
+            value: expanded
+                .components
+                .get(id)
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        }),
+        None => (),
+    }
+}
+
+/// Reports on the queries of `expanded` tagged with one of `tags`, sorted by id.
+///
+/// `expanded` is `raw` after expansion, and `errors` what the expansion returned: scripts aren't
+/// run again, the report shows what `build` outputs.
+pub fn query_reports(
+    raw: &QStore,
+    expanded: &QStore,
+    errors: &[(Id, Q3Error)],
+    tags: &[String],
+) -> Vec<QueryReport> {
+    let mut queries: Vec<&Q3Components> = expanded
+        .components
+        .values()
+        .filter(|component| matches!(component, Q3Components::Query(_)))
+        .filter(|component| component.metadata().matches_tags(tags))
+        .collect();
+    queries.sort_by(|a, b| a.get_id().0.cmp(&b.get_id().0));
+
+    queries
+        .into_iter()
+        .map(|component| {
+            let id = component.get_id();
+            let mut report = QueryReport {
+                id: id.clone(),
+                metadata: component.metadata().clone(),
+                query: Err(String::new()),
+                structure: None,
+                sources: Vec::new(),
+            };
+
+            match errors.iter().find(|(failed, _)| failed == id) {
+                Some((_, err)) => report.query = Err(err.to_string()),
+                None => {
+                    let query = component.to_string();
+
+                    collect_sources(raw, expanded, id, &mut report.sources, &mut Vec::new());
+                    report.structure = parse_boolean_query(&query)
+                        .ok()
+                        .map(|ast| PrettyPrinter::default().print(&ast));
+                    report.query = Ok(query);
+                }
+            }
+
+            report
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Status, owner and tags of a query on a single line
+fn details(metadata: &Metadata) -> String {
+    let mut details = vec![format!("Status: {}", metadata.status)];
+
+    if let Some(owner) = &metadata.owner {
+        details.push(format!("Owner: {owner}"));
+    }
+
+    if !metadata.tags.is_empty() {
+        details.push(format!("Tags: {}", metadata.tags.join(", ")));
+    }
+
+    details.join(" · ")
+}
+
+fn markdown(reports: &[QueryReport]) -> String {
+    let mut lines: Vec<String> = vec!["# Queries".into()];
+
+    for report in reports {
+        lines.push(format!("\n## {}\n", report.id));
+
+        if let Some(description) = &report.metadata.description {
+            lines.push(format!("{description}\n"));
+        }
+
+        lines.push(format!("{}\n", details(&report.metadata)));
+
+        match &report.query {
+            Ok(query) => lines.push(format!("```\n{query}\n```")),
+            Err(err) => {
+                lines.push(format!("**Failed to expand:** {err}"));
+                continue;
+            }
+        }
+
+        if let Some(structure) = &report.structure {
+            lines.push(format!("\n### Structure\n\n```\n{structure}\n```"));
+        }
+
+        for source in &report.sources {
+            lines.push(format!("\n### {} `{}`\n", source.kind, source.id));
+
+            for item in &source.items {
+                lines.push(format!("- `{item}`"));
+            }
+
+            lines.push(format!(
+                "{}Output: `{}`",
+                if source.items.is_empty() { "" } else { "\n" },
+                source.value
+            ));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn html(reports: &[QueryReport]) -> String {
+    let mut lines: Vec<String> = vec![
+        "<!DOCTYPE html>".into(),
+        "<html>".into(),
+        "<head><meta charset=\"utf-8\"><title>Queries</title></head>".into(),
+        "<body>".into(),
+        "<h1>Queries</h1>".into(),
+    ];
+
+    for report in reports {
+        lines.push(format!(
+            "<section id=\"{0}\">\n<h2>{0}</h2>",
+            escape_html(&report.id.0)
+        ));
+
+        if let Some(description) = &report.metadata.description {
+            lines.push(format!("<p>{}</p>", escape_html(description)));
+        }
+
+        lines.push(format!(
+            "<p><em>{}</em></p>",
+            escape_html(&details(&report.metadata))
+        ));
+
+        match &report.query {
+            Ok(query) => lines.push(format!("<pre><code>{}</code></pre>", escape_html(query))),
+            Err(err) => lines.push(format!(
+                "<p><strong>Failed to expand:</strong> {}</p>",
+                escape_html(err)
+            )),
+        }
+
+        if let Some(structure) = &report.structure {
+            lines.push(format!(
+                "<h3>Structure</h3>\n<pre><code>{}</code></pre>",
+                escape_html(structure)
+            ));
+        }
+
+        for source in &report.sources {
+            lines.push(format!(
+                "<h3>{} <code>{}</code></h3>",
+                source.kind,
+                escape_html(&source.id.0)
+            ));
+
+            if !source.items.is_empty() {
+                lines.push("<ul>".into());
+                for item in &source.items {
+                    lines.push(format!("<li><code>{}</code></li>", escape_html(item)));
+                }
+                lines.push("</ul>".into());
+            }
+
+            lines.push(format!(
+                "<p>Output: <code>{}</code></p>",
+                escape_html(&source.value)
+            ));
+        }
+
+        lines.push("</section>".into());
+    }
+
+    lines.push("</body>".into());
+    lines.push("</html>".into());
+
+    lines.join("\n") + "\n"
+}
+
+/// Renders a report of the queries of an unexpanded store tagged with one of `tags`
+pub fn report(
+    raw: &QStore,
+    expanded: &QStore,
+    errors: &[(Id, Q3Error)],
+    tags: &[String],
+    format: ReportFormat,
+) -> String {
+    let reports = query_reports(raw, expanded, errors, tags);
+
+    match format {
+        ReportFormat::Md => markdown(&reports),
+        ReportFormat::Html => html(&reports),
+    }
+}
+
+#[test]
+fn test_report() {
+    use crate::{Generator, Limits, List, Query, ScriptLang};
+
+    let mut store = QStore::new();
+    store.insert(Q3Components::List(List {
+        id: Id("words".into()),
+        value: "lorem ipsum".into(),
+        separator: " ".into(),
+        script: None,
+        script_lang: ScriptLang::default(),
+        output: None,
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+    store.insert(Q3Components::Query(
+        Query::new("title", "title:(#{words}) AND <b>")
+            .unwrap()
+            .with_metadata(Metadata {
+                description: Some("Titles".into()),
+                tags: vec!["title".into()],
+                ..Metadata::default()
+            }),
+    ));
+    store.insert(Q3Components::Query(
+        Query::new("broken", "#{missing}").unwrap(),
+    ));
+
+    store.insert(Q3Components::Generator(Generator {
+        id: Id("year".into()),
+        script: "raise Exception()".into(),
+        script_lang: ScriptLang::Python,
+        value: None,
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+    store.insert(Q3Components::Query(
+        Query::new("recent", "#{title} AND year:#{year}").unwrap(),
+    ));
+
+    // Stands for a generator expanded by `build`, its script must not run again
+    let mut expanded = store.clone();
+    if let Some(Q3Components::Generator(mut generator)) = expanded.get("year") {
+        generator.value = Some("2020".into());
+        expanded.insert(Q3Components::Generator(generator));
+    }
+    let errors = expanded.expand_all();
+
+    let reports = query_reports(&store, &expanded, &errors, &[]);
+
+    assert_eq!(reports[0].id, Id("broken".into()));
+    assert!(reports[0].query.is_err());
+    assert_eq!(reports[2].metadata.description, Some("Titles".into()));
+    assert_eq!(reports[2].query, Ok("title:(lorem ipsum) AND <b>".into()));
+    assert_eq!(
+        reports[1].query,
+        Ok("title:(lorem ipsum) AND <b> AND year:2020".into())
+    );
+    assert_eq!(
+        reports[1]
+            .sources
+            .iter()
+            .map(|source| (source.id.0.as_str(), source.value.as_str()))
+            .collect::<Vec<_>>(),
+        vec![("words", "lorem ipsum"), ("year", "2020")]
+    );
+    assert_eq!(reports[2].sources[0].items, vec!["lorem", "ipsum"]);
+
+    assert_eq!(
+        query_reports(&store, &expanded, &errors, &["title".into()]).len(),
+        1
+    );
+
+    let markdown = report(&store, &expanded, &errors, &[], ReportFormat::Md);
+    assert!(markdown.contains("## title\n\nTitles\n\nStatus: active · Tags: title\n"));
+    assert!(markdown.contains("- `ipsum`"));
+
+    let html = report(&store, &expanded, &errors, &[], ReportFormat::Html);
+    assert!(html.contains("<pre><code>title:(lorem ipsum) AND &lt;b&gt;</code></pre>"));
+}
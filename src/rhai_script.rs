@@ -0,0 +1,66 @@
+use rhai::{Array, Dynamic, Engine, Scope};
+
+use crate::{script, Q3Error};
+
+fn strings(items: Array) -> Vec<String> {
+    items.into_iter().map(|item| item.to_string()).collect()
+}
+
+fn array(items: Vec<String>) -> Array {
+    items.into_iter().map(Dynamic::from).collect()
+}
+
+/// Engine with the helpers of the `q3` Python module registered as global functions
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .register_fn("quote", |items: Array| array(script::quote(strings(items))))
+        .register_fn("trim", |items: Array| array(script::trim(strings(items))))
+        .register_fn("normalize_spaces", |items: Array| {
+            array(script::normalize_spaces(strings(items)))
+        })
+        .register_fn("uniq", |items: Array| array(script::uniq(strings(items))))
+        .register_fn("join_or", |items: Array| script::join_or(strings(items)))
+        .register_fn("join_and", |items: Array| script::join_and(strings(items)));
+
+    engine
+}
+
+/// Runs a Rhai script with `value` bound to the items of a list, or to `()` for a generator,
+/// and returns the string the script assigned to `value`
+pub(crate) fn run(script: &str, value: Option<Vec<&str>>) -> Result<String, Q3Error> {
+    let mut scope = Scope::new();
+
+    match value {
+        Some(items) => scope.push(
+            "value",
+            array(items.into_iter().map(String::from).collect()),
+        ),
+        None => scope.push("value", ()),
+    };
+
+    engine()
+        .run_with_scope(&mut scope, script)
+        .map_err(|err| Q3Error::RhaiScriptFailed(err.to_string()))?;
+
+    scope
+        .get("value")
+        .and_then(|value| value.clone().into_string().ok())
+        .ok_or_else(|| Q3Error::RhaiScriptFailed("`value` must be assigned a string".into()))
+}
+
+#[test]
+fn test_run_rhai_script() {
+    assert_eq!(
+        run(
+            "value = join_or(quote(uniq(normalize_spaces(value))));",
+            Some(vec!["lorem  ipsum", "lorem  ipsum", "dolor"])
+        )
+        .unwrap(),
+        "\"lorem ipsum\" OR \"dolor\""
+    );
+    assert_eq!(run("value = `from ${40 + 2}`;", None).unwrap(), "from 42");
+    assert!(run("let x = 1;", None).is_err());
+    assert!(run("value = ", None).is_err());
+}
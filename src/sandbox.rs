@@ -0,0 +1,134 @@
+use std::sync::RwLock;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+/// Builds the globals of sandboxed scripts, and rejects scripts reaching for the internals of
+/// Python objects or for frames, the usual ways out of restricted builtins
+const POLICY: &str = r#"
+import ast
+import builtins
+
+SAFE_BUILTINS = [
+    "abs", "all", "any", "bool", "chr", "dict", "divmod", "enumerate", "filter", "float",
+    "frozenset", "int", "isinstance", "iter", "len", "list", "map", "max", "min",
+    "next", "ord", "pow", "print", "range", "repr", "reversed", "round", "set", "slice",
+    "sorted", "str", "sum", "tuple", "zip", "ArithmeticError", "Exception", "IndexError",
+    "KeyError", "LookupError", "TypeError", "ValueError", "ZeroDivisionError",
+]
+
+# Frames lead back to the globals and builtins of the code calling the script
+FORBIDDEN_ATTRIBUTES = {
+    "ag_code", "ag_frame", "cr_code", "cr_frame", "f_back", "f_builtins", "f_code",
+    "f_globals", "f_locals", "gi_code", "gi_frame", "gi_yieldfrom", "tb_frame", "tb_next",
+}
+
+
+def check(script):
+    for node in ast.walk(ast.parse(script)):
+        name = None
+
+        if isinstance(node, ast.Attribute):
+            name = node.attr
+        elif isinstance(node, ast.Name):
+            name = node.id
+        elif isinstance(node, ast.alias):
+            # `from json import _private`, `import json as _json` and `import json._private`
+            names = [*node.name.split("."), node.asname or ""]
+            name = next((name for name in names if name.startswith("_")), None)
+
+        if name is not None and (name.startswith("_") or name in FORBIDDEN_ATTRIBUTES):
+            raise PermissionError(f"`{name}` is not allowed in the sandbox")
+
+
+def sandbox_globals(allowed_modules):
+    allowed = {"q3", *allowed_modules}
+
+    def restricted_import(name, globals=None, locals=None, fromlist=(), level=0):
+        if level != 0 or name.split(".")[0] not in allowed:
+            raise ImportError(f"import of `{name}` is not allowed in the sandbox")
+
+        return builtins.__import__(name, globals, locals, fromlist, level)
+
+    safe = {name: getattr(builtins, name) for name in SAFE_BUILTINS}
+    safe["__import__"] = restricted_import
+
+    return {"__builtins__": safe}
+"#;
+
+static SANDBOX: RwLock<Option<Sandbox>> = RwLock::new(None);
+
+/// Restrictions on the scripts of lists and generators: a few safe builtins, no access to names
+/// or attributes starting with `_`, and no imports but the `q3` module and `allowed_modules`.
+///
+/// Allowed modules expose whatever they import themselves, only allow the ones you trust.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Sandbox {
+    /// Modules scripts may import along with their submodules
+    pub allowed_modules: Vec<String>,
+}
+
+impl Sandbox {
+    /// Globals a script runs with, once checked against the sandbox
+    pub(crate) fn globals<'py>(
+        &self,
+        py: Python<'py>,
+        script: &str,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let policy = PyModule::from_code_bound(py, POLICY, "q3_sandbox.py", "q3_sandbox")?;
+
+        policy.call_method1("check", (script,))?;
+
+        Ok(policy
+            .call_method1("sandbox_globals", (self.allowed_modules.clone(),))?
+            .downcast_into::<PyDict>()?)
+    }
+}
+
+/// Runs the scripts of lists and generators in `sandbox` from now on
+pub fn enable(sandbox: Sandbox) {
+    *SANDBOX.write().unwrap_or_else(|err| err.into_inner()) = Some(sandbox);
+}
+
+/// Sandbox scripts run in, if enabled
+pub(crate) fn current() -> Option<Sandbox> {
+    SANDBOX
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+#[test]
+fn test_sandbox() {
+    pyo3::prepare_freethreaded_python();
+
+    let sandbox = Sandbox {
+        allowed_modules: vec!["json".into()],
+    };
+
+    Python::with_gil(|py| {
+        let run = |script: &str| {
+            let globals = sandbox.globals(py, script)?;
+            py.run_bound(script, Some(&globals), None)?;
+
+            globals
+                .get_item("value")
+                .map(|value| value.map(|value| value.to_string()))
+        };
+
+        assert_eq!(
+            run("import json.decoder\nvalue = json.dumps(sorted([2, 1]))").unwrap(),
+            Some("[1, 2]".into())
+        );
+        assert!(run("import os")
+            .unwrap_err()
+            .is_instance_of::<pyo3::exceptions::PyImportError>(py));
+        assert!(run("value = ().__class__").is_err());
+        assert!(run("value = (x for x in []).gi_frame").is_err());
+        assert!(run("value = open('/etc/passwd')").is_err());
+        assert!(run("from json import _default_decoder").is_err());
+        assert!(run("import json as _json").is_err());
+        assert!(run("import json._private").is_err());
+    });
+}
@@ -21,7 +21,7 @@ pub fn trim(mut input: Vec<String>) -> Vec<String> {
 #[pyfunction]
 pub fn normalize_spaces(mut input: Vec<String>) -> Vec<String> {
     input.iter_mut().for_each(|elem| {
-        *elem = elem.split_whitespace().intersperse(" ").collect();
+        *elem = elem.split_whitespace().collect::<Vec<&str>>().join(" ");
     });
 
     input
@@ -43,8 +43,8 @@ pub fn join_and(input: Vec<String>) -> String {
     input.join(" AND ")
 }
 
-#[pymodule]
-pub fn q3(q3_module: &Bound<'_, PyModule>) -> PyResult<()> {
+/// Adds the helpers scripts use to transform list items to a Python module
+pub fn add_helpers(q3_module: &Bound<'_, PyModule>) -> PyResult<()> {
     q3_module.add_function(wrap_pyfunction!(quote, q3_module)?)?;
     q3_module.add_function(wrap_pyfunction!(join_or, q3_module)?)?;
     q3_module.add_function(wrap_pyfunction!(join_and, q3_module)?)?;
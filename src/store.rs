@@ -1,13 +1,16 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::sync::Mutex;
 
 use crate::error::Q3Error;
 use crate::{Expand, Id, Identify, Q3Components, Query};
 
 /// A struct that holds queries and lists
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct QStore {
     failed_expansions: HashSet<Id>,
+    /// Components that could not be expanded
+    failed_components: HashSet<Id>,
     pub components: HashMap<Id, Q3Components>,
 }
 
@@ -16,6 +19,7 @@ impl QStore {
         Self {
             components: HashMap::default(),
             failed_expansions: HashSet::new(),
+            failed_components: HashSet::new(),
         }
     }
 
@@ -44,6 +48,210 @@ impl QStore {
     pub fn remove_failed_expansion(&mut self, id: &Id) {
         self.failed_expansions.remove(id);
     }
+
+    pub fn is_failed(&self, id: &Id) -> bool {
+        self.failed_components.contains(id)
+    }
+
+    /// Expands every component, carrying on when a component fails to expand.
+    ///
+    /// Failed components are marked as such and returned with the cause of their failure, sorted
+    /// by id. Components referencing a failed component fail as well.
+    pub fn expand_all(&mut self) -> Vec<(Id, Q3Error)> {
+        self.expand_all_jobs(1)
+    }
+
+    /// Same as [`QStore::expand_all`], expanding up to `jobs` independent components at a time.
+    ///
+    /// Scripts still take turns holding the Python lock, unless they run in worker processes,
+    /// see [`crate::worker`].
+    pub fn expand_all_jobs(&mut self, jobs: usize) -> Vec<(Id, Q3Error)> {
+        let mut ids: Vec<Id> = self.components.keys().cloned().collect();
+        ids.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut errors: Vec<(Id, Q3Error)> = Vec::new();
+
+        // Lists and generators don't depend on other components, they are expanded first
+        let sources: Vec<Id> = ids
+            .iter()
+            .filter(|id| !matches!(self.components.get(*id), Some(Q3Components::Query(_))))
+            .cloned()
+            .collect();
+        let expanded = self.expand_batch(&sources, jobs);
+        self.apply(expanded, &mut errors);
+
+        loop {
+            let raw: Vec<Id> = ids
+                .iter()
+                .filter(|id| !self.is_failed(id) && self.is_raw_query(id))
+                .cloned()
+                .collect();
+
+            // Queries are ready once the queries they reference are expanded or failed
+            let ready: Vec<Id> = raw
+                .iter()
+                .filter(|id| {
+                    self.components[*id]
+                        .dependencies()
+                        .iter()
+                        .all(|dependency| {
+                            self.is_failed(dependency) || !self.is_raw_query(dependency)
+                        })
+                })
+                .cloned()
+                .collect();
+
+            // When none is ready, the remaining queries reference each other and fail
+            let batch = match ready.is_empty() {
+                true => raw,
+                false => ready,
+            };
+
+            if batch.is_empty() {
+                break;
+            }
+
+            let expanded = self.expand_batch(&batch, jobs);
+
+            if !self.apply(expanded, &mut errors) {
+                break;
+            }
+        }
+
+        for id in &ids {
+            if !self.is_failed(id) && self.is_raw_query(id) {
+                self.failed_components.insert(id.clone());
+                errors.push((id.clone(), Q3Error::FailedToExpand(id.clone())));
+            }
+        }
+
+        errors.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+        errors
+    }
+
+    fn is_raw_query(&self, id: &Id) -> bool {
+        matches!(
+            self.components.get(id),
+            Some(Q3Components::Query(Query::Raw { .. }))
+        )
+    }
+
+    /// Expands copies of the components `ids` against the store, on up to `jobs` threads
+    fn expand_batch(&self, ids: &[Id], jobs: usize) -> Vec<(Id, Result<Q3Components, Q3Error>)> {
+        let expand = |id: &Id| {
+            let mut component = self.components.get(id).cloned()?;
+            let result = component.expand(self.clone()).map(|_| component);
+
+            Some((id.clone(), result))
+        };
+
+        // Stays on the calling thread, which may hold the Python lock
+        if jobs <= 1 {
+            return ids.iter().filter_map(expand).collect();
+        }
+
+        let pending = Mutex::new(ids.iter());
+        let results = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.min(ids.len()) {
+                scope.spawn(|| loop {
+                    let next = pending.lock().unwrap().next();
+
+                    let Some(id) = next else {
+                        break;
+                    };
+
+                    if let Some(result) = expand(id) {
+                        results.lock().unwrap().push(result);
+                    }
+                });
+            }
+        });
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+
+        results
+    }
+
+    /// Stores the results of [`QStore::expand_batch`], returning whether any component was
+    /// expanded or failed
+    fn apply(
+        &mut self,
+        results: Vec<(Id, Result<Q3Components, Q3Error>)>,
+        errors: &mut Vec<(Id, Q3Error)>,
+    ) -> bool {
+        let mut progress = false;
+
+        for (id, result) in results {
+            match result {
+                Ok(component) => {
+                    progress |= !matches!(component, Q3Components::Query(Query::Raw { .. }));
+                    self.insert(component);
+                }
+                Err(err) => {
+                    self.failed_components.insert(id.clone());
+                    errors.push((id, err));
+                    progress = true;
+                }
+            }
+        }
+
+        progress
+    }
+
+    /// Returns `ids` along with every component that transitively references one of them
+    pub fn affected_by(&self, ids: &HashSet<Id>) -> HashSet<Id> {
+        let mut affected = ids.clone();
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for (id, component) in &self.components {
+                if !affected.contains(id)
+                    && component
+                        .dependencies()
+                        .iter()
+                        .any(|dependency| affected.contains(dependency))
+                {
+                    affected.insert(id.clone());
+                    changed = true;
+                }
+            }
+        }
+
+        affected
+    }
+
+    /// Returns `id` along with every component it transitively references
+    pub fn dependencies_of(&self, id: &Id) -> HashSet<Id> {
+        let mut dependencies = HashSet::from([id.clone()]);
+        let mut pending = vec![id.clone()];
+
+        while let Some(id) = pending.pop() {
+            if let Some(component) = self.components.get(&id) {
+                for dependency in component.dependencies() {
+                    if dependencies.insert(dependency.clone()) {
+                        pending.push(dependency);
+                    }
+                }
+            }
+        }
+
+        dependencies
+    }
+
+    /// Returns a copy of the store without its failed components
+    pub fn without_failed(&self) -> Self {
+        let mut store = self.clone();
+        store
+            .components
+            .retain(|id, _| !self.failed_components.contains(id));
+
+        store
+    }
 }
 
 impl Display for QStore {
@@ -61,19 +269,66 @@ impl Display for QStore {
 impl Expand for QStore {
     type State = Self;
 
-    fn expand(&mut self, mut state: Self::State) -> Result<Self::State, Q3Error> {
-        while self
-            .components
-            .values()
-            .any(|value| matches!(value, Q3Components::Query(Query::Raw { .. })))
-        {
-            for component in self.components.values_mut() {
-                component.expand(state.clone())?;
-            }
+    fn expand(&mut self, _state: Self::State) -> Result<Self::State, Q3Error> {
+        let errors = self.expand_all();
 
-            state = self.clone();
+        match errors.is_empty() {
+            true => Ok(self.clone()),
+            false => Err(Q3Error::ExpansionFailed(errors)),
         }
+    }
+}
+
+#[test]
+fn test_expand_all_jobs() {
+    let mut store = QStore::new();
 
-        Ok(state)
+    for (id, query) in [
+        ("q1", "lorem #{q2} #{q3}"),
+        ("q2", "ipsum #{q4}"),
+        ("q3", "dolor #{q4}"),
+        ("q4", "sit"),
+        ("q5", "#{missing}"),
+        ("q6", "#{q5}"),
+    ] {
+        store.insert(Q3Components::Query(Query::new(id, query).unwrap()));
     }
+
+    let mut sequential = store.clone();
+    let errors = sequential.expand_all();
+
+    let mut parallel = store.clone();
+    let parallel_errors = parallel.expand_all_jobs(4);
+
+    assert_eq!(parallel, sequential);
+    assert_eq!(format!("{errors:?}"), format!("{parallel_errors:?}"));
+    assert_eq!(
+        parallel.get("q1").unwrap().to_string(),
+        "lorem ipsum sit dolor sit"
+    );
+    assert!(matches!(errors[1], (_, Q3Error::DependencyFailed(_))));
+}
+
+#[test]
+fn test_affected_by() {
+    let mut store = QStore::new();
+
+    for (id, query) in [
+        ("q1", "lorem #{q2}"),
+        ("q2", "ipsum #{q3}"),
+        ("q3", "dolor"),
+        ("q4", "sit #{q3}"),
+        ("q5", "amet"),
+    ] {
+        store.insert(Q3Components::Query(Query::new(id, query).unwrap()));
+    }
+
+    let ids = |ids: &[&str]| -> HashSet<Id> { ids.iter().map(|id| Id(id.to_string())).collect() };
+
+    assert_eq!(
+        store.affected_by(&ids(&["q3"])),
+        ids(&["q1", "q2", "q3", "q4"])
+    );
+    assert_eq!(store.affected_by(&ids(&["q2"])), ids(&["q1", "q2"]));
+    assert_eq!(store.affected_by(&ids(&["q5"])), ids(&["q5"]));
 }
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::cache::cached;
+use crate::parser::Q3Ast;
+use crate::worker::trace_script;
+use crate::{Id, Q3Components, Q3Error, QStore, Query};
+
+/// Runs a script one top level statement at a time, recording `value` after each of them.
+/// Imports are run but not recorded.
+pub(crate) const TRACER: &str = r#"
+import ast
+
+stages = []
+for node in ast.parse(script).body:
+    exec(compile(ast.Module(body=[node], type_ignores=[]), "<q3>", "exec"), scope)
+
+    if not isinstance(node, (ast.Import, ast.ImportFrom)):
+        value = scope.get("value")
+        stages.append((
+            ast.get_source_segment(script, node),
+            value if isinstance(value, str) else repr(value),
+        ))
+"#;
+
+/// Script statements along with the value they produced
+pub(crate) type Stages = Vec<(String, String)>;
+
+/// How a component was expanded
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceNode {
+    pub id: Id,
+    pub kind: &'static str,
+    /// Raw template of a query
+    pub template: Option<String>,
+    /// Items of a list, before its script runs
+    pub items: Vec<String>,
+    /// Script statements along with the value they produced
+    pub stages: Stages,
+    /// Traces of the references of a query, in order of appearance
+    pub references: Vec<TraceNode>,
+    pub value: String,
+}
+
+/// Expands the component `id` of an unexpanded store, recording every step.
+///
+/// Components referenced several times are traced once. Components already expanded in the store
+/// keep their value, without stages.
+pub fn trace(store: &QStore, id: &Id) -> Result<TraceNode, Q3Error> {
+    trace_component(store, id, &mut Vec::new(), &mut HashMap::new())
+}
+
+/// Traces a script like [`trace_script`], reading the trace from the cache if it holds it
+fn cached_trace(
+    parts: &[&str],
+    run: impl FnOnce() -> Result<(Stages, String), Q3Error>,
+) -> Result<(Stages, String), Q3Error> {
+    let invalid = |err: serde_json::Error| Q3Error::ScriptWorkerFailed(err.to_string());
+
+    let trace = cached(parts, || serde_json::to_string(&run()?).map_err(invalid))?;
+
+    serde_json::from_str(&trace).map_err(invalid)
+}
+
+fn trace_component(
+    store: &QStore,
+    id: &Id,
+    stack: &mut Vec<Id>,
+    traced: &mut HashMap<Id, TraceNode>,
+) -> Result<TraceNode, Q3Error> {
+    if stack.contains(id) {
+        return Err(Q3Error::FailedToExpand(id.clone()));
+    }
+
+    if let Some(node) = traced.get(id) {
+        return Ok(node.clone());
+    }
+
+    let component = store
+        .get(id.to_string())
+        .ok_or_else(|| Q3Error::IdNotFound(id.to_string()))?;
+
+    let mut node = TraceNode {
+        id: id.clone(),
+        kind: component.kind(),
+        template: None,
+        items: Vec::new(),
+        stages: Vec::new(),
+        references: Vec::new(),
+        value: String::new(),
+    };
+
+    match component {
+        Q3Components::Query(query) => {
+            let (template, tokens) = match query {
+                Query::Raw { query, tokens, .. } | Query::Expanded { query, tokens, .. } => {
+                    (query, tokens)
+                }
+            };
+
+            stack.push(id.clone());
+
+            for token in tokens {
+                match token {
+                    Q3Ast::Other(text) => node.value.push_str(&text),
+                    Q3Ast::Id(reference) => {
+                        let reference = trace_component(store, &Id(reference), stack, traced)?;
+                        node.value.push_str(&reference.value);
+                        node.references.push(reference);
+                    }
+                }
+            }
+
+            stack.pop();
+            node.template = Some(template);
+        }
+        Q3Components::List(list) => {
+            node.items = list.items().into_iter().map(String::from).collect();
+
+            match (&list.output, &list.script) {
+                (Some(output), _) => node.value = output.clone(),
+                (None, Some(script)) => {
+                    let lang = list.script_lang.to_string();
+                    let mut key: Vec<&str> = vec!["trace", "list", &lang, script];
+                    key.extend(list.items());
+
+                    (node.stages, node.value) = cached_trace(&key, || {
+                        trace_script(
+                            id,
+                            script,
+                            list.script_lang,
+                            Some(list.items()),
+                            &list.limits,
+                        )
+                    })?
+                }
+                (None, None) => node.value = list.value,
+            }
+        }
+        Q3Components::Generator(generator) => match generator.value {
+            Some(value) => node.value = value,
+            None => {
+                let lang = generator.script_lang.to_string();
+
+                (node.stages, node.value) =
+                    cached_trace(&["trace", "generator", &lang, &generator.script], || {
+                        trace_script(
+                            id,
+                            &generator.script,
+                            generator.script_lang,
+                            None,
+                            &generator.limits,
+                        )
+                    })?;
+            }
+        },
+    }
+
+    traced.insert(id.clone(), node.clone());
+
+    Ok(node)
+}
+
+impl TraceNode {
+    fn lines(&self) -> Vec<String> {
+        let mut entries: Vec<Vec<String>> = Vec::new();
+
+        if let Some(template) = &self.template {
+            entries.push(vec![format!("template: {template}")]);
+        }
+
+        if self.kind == "list" {
+            entries.push(vec![format!("items: {:?}", self.items)]);
+        }
+
+        for (statement, value) in &self.stages {
+            entries.push(vec![format!("{} -> {value}", statement.replace('\n', " "))]);
+        }
+
+        for reference in &self.references {
+            entries.push(reference.lines());
+        }
+
+        entries.push(vec![format!("=> {}", self.value)]);
+
+        let mut lines = vec![format!("{} ({})", self.id, self.kind)];
+        let last = entries.len() - 1;
+
+        for (index, entry) in entries.into_iter().enumerate() {
+            let (first, rest) = match index == last {
+                true => ("└─ ", "   "),
+                false => ("├─ ", "│  "),
+            };
+
+            for (line_index, line) in entry.into_iter().enumerate() {
+                let prefix = if line_index == 0 { first } else { rest };
+                lines.push(format!("{prefix}{line}"));
+            }
+        }
+
+        lines
+    }
+}
+
+impl Display for TraceNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.lines().join("\n"))
+    }
+}
+
+#[test]
+fn test_trace() {
+    use crate::{Generator, Limits, List, Metadata, ScriptLang};
+
+    let mut store = QStore::new();
+    store.insert(Q3Components::List(List {
+        id: Id("words".into()),
+        value: "lorem ipsum".into(),
+        separator: " ".into(),
+        script: None,
+        script_lang: ScriptLang::default(),
+        output: None,
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+    store.insert(Q3Components::Query(
+        Query::new("title", "title:(#{words})").unwrap(),
+    ));
+    store.insert(Q3Components::Query(
+        Query::new("full", "#{title} OR #{words}").unwrap(),
+    ));
+
+    let node = trace(&store, &Id("full".into())).unwrap();
+
+    assert_eq!(node.value, "title:(lorem ipsum) OR lorem ipsum");
+    assert_eq!(
+        node.to_string(),
+        [
+            "full (query)",
+            "├─ template: #{title} OR #{words}",
+            "├─ title (query)",
+            "│  ├─ template: title:(#{words})",
+            "│  ├─ words (list)",
+            "│  │  ├─ items: [\"lorem\", \"ipsum\"]",
+            "│  │  └─ => lorem ipsum",
+            "│  └─ => title:(lorem ipsum)",
+            "├─ words (list)",
+            "│  ├─ items: [\"lorem\", \"ipsum\"]",
+            "│  └─ => lorem ipsum",
+            "└─ => title:(lorem ipsum) OR lorem ipsum",
+        ]
+        .join("\n")
+    );
+
+    // Scripts are traced the way they are expanded, their limits need a worker here too
+    store.insert(Q3Components::List(List {
+        id: Id("words".into()),
+        value: "lorem ipsum".into(),
+        separator: " ".into(),
+        script: Some("value = ' '.join(value)".into()),
+        script_lang: ScriptLang::default(),
+        output: None,
+        limits: Limits {
+            timeout: Some(1.0),
+            ..Limits::default()
+        },
+        metadata: Metadata::default(),
+    }));
+    assert!(matches!(
+        trace(&store, &Id("full".into())),
+        Err(Q3Error::ScriptWorkerFailed(_))
+    ));
+
+    store.insert(Q3Components::Query(Query::new("words", "#{full}").unwrap()));
+    assert!(trace(&store, &Id("full".into())).is_err());
+
+    // Components referenced twice are traced once
+    crate::init_python().unwrap();
+    store.insert(Q3Components::Generator(Generator {
+        id: Id("random".into()),
+        script: "import uuid\nvalue = str(uuid.uuid4())".into(),
+        script_lang: ScriptLang::default(),
+        value: None,
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+    store.insert(Q3Components::Query(
+        Query::new("twice", "#{random} #{random}").unwrap(),
+    ));
+
+    let node = trace(&store, &Id("twice".into())).unwrap();
+    assert_eq!(node.references[0], node.references[1]);
+    assert_eq!(node.references[0].stages.len(), 1);
+
+    // Expanded components keep their value
+    store.insert(Q3Components::List(List {
+        id: Id("words".into()),
+        value: "lorem ipsum".into(),
+        separator: " ".into(),
+        script: Some("value = ' '.join(value)".into()),
+        script_lang: ScriptLang::default(),
+        output: Some("dolor".into()),
+        limits: Limits::default(),
+        metadata: Metadata::default(),
+    }));
+
+    let node = trace(&store, &Id("words".into())).unwrap();
+    assert_eq!(node.value, "dolor");
+    assert!(node.stages.is_empty());
+}
@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use super::highlight::highlight;
+use q3::output::{output_path, OutputFormat};
+use q3::parser::{parse_boolean_query, PrettyPrinter};
+use q3::{Id, Identify, Q3Error, QStore};
+
+/// A component as displayed by the browser
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub id: Id,
+    pub kind: &'static str,
+    /// Expanded value of the component, or the cause of its failure
+    pub value: Result<String, String>,
+    pub dependencies: Vec<Id>,
+    pub dependents: Vec<Id>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Browse,
+    Search,
+}
+
+/// State of the component browser
+#[derive(Debug)]
+pub struct Browser {
+    entries: Vec<Entry>,
+    /// Indexes of the entries matching the search
+    visible: Vec<usize>,
+    list_state: ListState,
+    search: String,
+    mode: Mode,
+    pretty: bool,
+    scroll: u16,
+    status: String,
+    out_dir: PathBuf,
+    /// Output paths set on the queries, relative to `out_dir`
+    outputs: HashMap<Id, PathBuf>,
+}
+
+impl Browser {
+    /// Builds the browser from a store before expansion, `raw`, and the same store once expanded
+    pub fn new(
+        raw: &QStore,
+        expanded: &QStore,
+        errors: Vec<(Id, Q3Error)>,
+        out_dir: PathBuf,
+        outputs: HashMap<Id, PathBuf>,
+    ) -> Self {
+        let mut entries: Vec<Entry> = raw
+            .components
+            .values()
+            .map(|component| {
+                let id = component.get_id().clone();
+
+                let value = match errors.iter().find(|(failed_id, _)| *failed_id == id) {
+                    Some((_, err)) => Err(err.to_string()),
+                    None => Ok(expanded
+                        .components
+                        .get(&id)
+                        .map(|component| component.to_string())
+                        .unwrap_or_default()),
+                };
+
+                let mut dependents: Vec<Id> = raw
+                    .components
+                    .values()
+                    .filter(|other| other.dependencies().contains(&id))
+                    .map(|other| other.get_id().clone())
+                    .collect();
+                dependents.sort_by(|a, b| a.0.cmp(&b.0));
+
+                Entry {
+                    kind: component.kind(),
+                    dependencies: component.dependencies(),
+                    dependents,
+                    value,
+                    id,
+                }
+            })
+            .collect();
+
+        entries.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+
+        let mut browser = Self {
+            visible: (0..entries.len()).collect(),
+            entries,
+            list_state: ListState::default(),
+            search: String::new(),
+            mode: Mode::Browse,
+            pretty: false,
+            scroll: 0,
+            status: String::new(),
+            out_dir,
+            outputs,
+        };
+
+        browser.filter();
+        browser
+    }
+
+    /// Only keeps the entries whose id contains the search
+    fn filter(&mut self) {
+        let search = self.search.to_lowercase();
+
+        self.visible = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.id.0.to_lowercase().contains(&search))
+            .map(|(index, _)| index)
+            .collect();
+
+        self.list_state.select(match self.visible.is_empty() {
+            true => None,
+            false => Some(0),
+        });
+        self.scroll = 0;
+    }
+
+    pub fn selected(&self) -> Option<&Entry> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.visible.get(index))
+            .map(|index| &self.entries[*index])
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+
+        let index = self.list_state.selected().unwrap_or_default() as isize + offset;
+        self.list_state.select(Some(
+            index.clamp(0, self.visible.len() as isize - 1) as usize
+        ));
+        self.scroll = 0;
+    }
+
+    fn selected_text(&self) -> Option<String> {
+        let value = self.selected()?.value.as_ref().ok()?;
+
+        match self.pretty {
+            true => Some(
+                parse_boolean_query(value)
+                    .map(|ast| PrettyPrinter::default().print(&ast))
+                    .unwrap_or_else(|_| value.clone()),
+            ),
+            false => Some(value.clone()),
+        }
+    }
+
+    /// Writes the selected component to its output path in `out_dir`, `<id>.txt` by default
+    fn write_selected(&mut self) {
+        let (Some(entry), Some(text)) = (self.selected(), self.selected_text()) else {
+            return;
+        };
+
+        let path = match output_path(&entry.id, &self.outputs, OutputFormat::Txt) {
+            Ok(path) => self.out_dir.join(path),
+            Err(err) => {
+                self.status = err.to_string();
+                return;
+            }
+        };
+
+        let result = match path.parent() {
+            Some(parent) => std::fs::create_dir_all(parent),
+            None => Ok(()),
+        };
+
+        self.status = match result.and_then(|()| std::fs::write(&path, text)) {
+            Ok(()) => format!("wrote {}", path.display()),
+            Err(err) => format!("failed to write {}: {err}", path.display()),
+        };
+    }
+
+    /// Handles a key press, returns false when the browser should exit
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        match (self.mode, key) {
+            (Mode::Search, KeyCode::Enter) => self.mode = Mode::Browse,
+            (Mode::Search, KeyCode::Esc) => {
+                self.search.clear();
+                self.filter();
+                self.mode = Mode::Browse;
+            }
+            (Mode::Search, KeyCode::Backspace) => {
+                self.search.pop();
+                self.filter();
+            }
+            (Mode::Search, KeyCode::Char(c)) => {
+                self.search.push(c);
+                self.filter();
+            }
+            (Mode::Browse, KeyCode::Char('q') | KeyCode::Esc) => return false,
+            (Mode::Browse, KeyCode::Char('/')) => {
+                self.mode = Mode::Search;
+                self.status.clear();
+            }
+            (Mode::Browse, KeyCode::Down | KeyCode::Char('j')) => self.move_selection(1),
+            (Mode::Browse, KeyCode::Up | KeyCode::Char('k')) => self.move_selection(-1),
+            (Mode::Browse, KeyCode::PageDown) => self.scroll = self.scroll.saturating_add(10),
+            (Mode::Browse, KeyCode::PageUp) => self.scroll = self.scroll.saturating_sub(10),
+            (Mode::Browse, KeyCode::Char('p')) => self.pretty = !self.pretty,
+            (Mode::Browse, KeyCode::Char('w')) => self.write_selected(),
+            _ => (),
+        }
+
+        true
+    }
+
+    pub fn run(mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .areas(frame.area());
+
+        let [components, details] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(25), Constraint::Percentage(75)])
+            .areas(main);
+
+        let [query, dependencies] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(4)])
+            .areas(details);
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|index| {
+                let entry = &self.entries[*index];
+                let style = match entry.value {
+                    Ok(_) => Style::default(),
+                    Err(_) => Style::default().fg(Color::Red),
+                };
+
+                ListItem::new(Line::from(vec![
+                    Span::styled(entry.id.to_string(), style),
+                    Span::styled(
+                        format!(" {}", entry.kind),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = ratatui::widgets::List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("components"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(list, components, &mut self.list_state);
+
+        let (title, text) = match (self.selected(), self.selected_text()) {
+            (Some(entry), Some(text)) => (entry.id.to_string(), highlight(&text)),
+            (Some(entry), None) => (
+                entry.id.to_string(),
+                Text::styled(
+                    entry.value.clone().err().unwrap_or_default(),
+                    Style::default().fg(Color::Red),
+                ),
+            ),
+            (None, _) => (String::new(), Text::default()),
+        };
+
+        frame.render_widget(
+            Paragraph::new(text)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0)),
+            query,
+        );
+
+        let join = |ids: &[Id]| {
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        let dependency_text = match self.selected() {
+            Some(entry) => Text::from(vec![
+                Line::from(format!("uses: {}", join(&entry.dependencies))),
+                Line::from(format!("used by: {}", join(&entry.dependents))),
+            ]),
+            None => Text::default(),
+        };
+
+        frame.render_widget(
+            Paragraph::new(dependency_text)
+                .block(Block::default().borders(Borders::ALL).title("dependencies")),
+            dependencies,
+        );
+
+        let status_line = match self.mode {
+            Mode::Search => format!("/{}", self.search),
+            Mode::Browse if !self.status.is_empty() => self.status.clone(),
+            Mode::Browse => {
+                "↑↓ select  / search  p pretty  w write to file  PgUp/PgDn scroll  q quit".into()
+            }
+        };
+
+        frame.render_widget(
+            Paragraph::new(status_line).style(Style::default().fg(Color::DarkGray)),
+            status,
+        );
+    }
+}
+
+#[test]
+fn test_browser_search() {
+    use q3::{Q3Components, Query};
+
+    let mut raw = QStore::new();
+    raw.insert(Q3Components::Query(Query::new("title", "lorem").unwrap()));
+    raw.insert(Q3Components::Query(
+        Query::new("abstract", "#{title}").unwrap(),
+    ));
+    raw.insert(Q3Components::Query(
+        Query::new("full", "#{abstract}").unwrap(),
+    ));
+
+    let mut expanded = raw.clone();
+    let errors = expanded.expand_all();
+
+    let mut browser = Browser::new(&raw, &expanded, errors, PathBuf::from("."), HashMap::new());
+
+    assert_eq!(browser.selected().unwrap().id, Id("abstract".into()));
+    assert_eq!(
+        browser.selected().unwrap().dependents,
+        vec![Id("full".into())]
+    );
+
+    for key in [KeyCode::Char('/'), KeyCode::Char('t'), KeyCode::Char('i')] {
+        browser.handle_key(key);
+    }
+
+    assert_eq!(browser.selected().unwrap().id, Id("title".into()));
+    assert_eq!(browser.selected().unwrap().value, Ok("lorem".into()));
+
+    browser.handle_key(KeyCode::Esc);
+    browser.handle_key(KeyCode::Down);
+
+    assert_eq!(browser.selected().unwrap().id, Id("full".into()));
+    assert!(!browser.handle_key(KeyCode::Char('q')));
+}
+
+#[test]
+fn test_write_selected() {
+    use q3::{Q3Components, Query};
+
+    let dir = std::env::temp_dir().join(format!("q3-browser-{}", std::process::id()));
+
+    let mut raw = QStore::new();
+    raw.insert(Q3Components::Query(
+        Query::new("../escaped", "lorem").unwrap(),
+    ));
+    raw.insert(Q3Components::Query(Query::new("title", "ipsum").unwrap()));
+
+    let mut expanded = raw.clone();
+    let errors = expanded.expand_all();
+
+    let outputs = HashMap::from([(Id("title".into()), PathBuf::from("queries/title.txt"))]);
+    let mut browser = Browser::new(&raw, &expanded, errors, dir.clone(), outputs);
+
+    browser.write_selected();
+    assert!(browser.status.starts_with("Invalid output path"));
+    assert!(!dir.exists());
+
+    browser.handle_key(KeyCode::Down);
+    browser.write_selected();
+    let written = std::fs::read_to_string(dir.join("queries/title.txt"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+    assert_eq!(written.unwrap(), "ipsum");
+}
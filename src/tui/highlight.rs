@@ -0,0 +1,109 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+
+const OPERATORS: [&str; 5] = ["AND", "OR", "NOT", "&&", "||"];
+
+fn operator_style() -> Style {
+    Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD)
+}
+
+/// Highlights a single line of a Lucene-like query. Highlighting is lexical so that queries that
+/// don't parse are still highlighted.
+fn highlight_line(line: &str) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '"' => {
+                let mut end = line.len();
+                let mut escaped = false;
+
+                for (index, c) in chars.by_ref() {
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => {
+                            end = index + 1;
+                            break;
+                        }
+                        _ => escaped = false,
+                    }
+                }
+
+                spans.push(Span::styled(
+                    line[start..end].to_string(),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            '(' | ')' | '[' | ']' | '{' | '}' => spans.push(Span::styled(
+                c.to_string(),
+                Style::default().fg(Color::Magenta),
+            )),
+            c if c.is_whitespace() => spans.push(Span::raw(c.to_string())),
+            _ => {
+                let mut end = line.len();
+
+                while let Some((index, c)) = chars.peek() {
+                    if c.is_whitespace() || "\"()[]{}".contains(*c) {
+                        end = *index;
+                        break;
+                    }
+                    chars.next();
+                }
+
+                let word = &line[start..end];
+
+                match word.split_once(':') {
+                    _ if OPERATORS.contains(&word) => {
+                        spans.push(Span::styled(word.to_string(), operator_style()))
+                    }
+                    Some((field, rest)) => {
+                        spans.push(Span::styled(
+                            format!("{field}:"),
+                            Style::default().fg(Color::Green),
+                        ));
+
+                        if !rest.is_empty() {
+                            spans.push(Span::raw(rest.to_string()));
+                        }
+                    }
+                    None => spans.push(Span::raw(word.to_string())),
+                }
+            }
+        }
+    }
+
+    Line::from(spans)
+}
+
+/// Highlights operators, fields, phrases and parentheses of a query
+pub fn highlight(query: &str) -> Text<'static> {
+    Text::from(query.lines().map(highlight_line).collect::<Vec<Line>>())
+}
+
+#[test]
+fn test_highlight() {
+    let line = highlight_line(r#"title:("lorem \" ipsum" OR dolor)"#);
+    let spans: Vec<&str> = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+
+    assert_eq!(
+        spans,
+        vec![
+            "title:",
+            "(",
+            r#""lorem \" ipsum""#,
+            " ",
+            "OR",
+            " ",
+            "dolor",
+            ")"
+        ]
+    );
+    assert_eq!(line.spans[4].style, operator_style());
+}
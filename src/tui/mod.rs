@@ -1,21 +1,23 @@
-use crate::QStore;
+use q3::corpus::{Document, Evaluation};
+use q3::{Id, Identify, Q3Components, Q3Error, QStore};
 
-use tabled::{Table, Tabled};
+use tabled::Tabled;
 
-use tabled::{
-    builder::Builder,
-    settings::{object::Rows, Alignment, Modify, Style, Width},
-};
+mod browser;
+pub use browser::Browser;
+
+mod highlight;
 
 #[derive(Tabled)]
 pub struct TableRow {
     pub id: String,
-    query: String,
+    pub query: String,
 }
 
-impl From<QStore> for Vec<TableRow> {
-    fn from(value: QStore) -> Self {
-        value
+impl TableRow {
+    /// One row per component of the store
+    pub fn from_store(store: QStore) -> Vec<Self> {
+        store
             .components
             .into_iter()
             .map(|(id, value)| TableRow {
@@ -25,3 +27,106 @@ impl From<QStore> for Vec<TableRow> {
             .collect()
     }
 }
+
+#[derive(Tabled)]
+pub struct HitsRow {
+    pub id: String,
+    pub hits: usize,
+    pub documents: String,
+}
+
+impl HitsRow {
+    pub fn new(id: &Id, documents: Vec<&Document>) -> Self {
+        Self {
+            id: id.to_string(),
+            hits: documents.len(),
+            documents: documents
+                .iter()
+                .map(|document| document.id.as_str())
+                .collect::<Vec<&str>>()
+                .join(", "),
+        }
+    }
+}
+
+#[derive(Tabled)]
+pub struct EvaluationRow {
+    pub id: String,
+    pub precision: String,
+    pub recall: String,
+    pub f1: String,
+    #[tabled(rename = "false positives")]
+    pub false_positives: String,
+    #[tabled(rename = "false negatives")]
+    pub false_negatives: String,
+}
+
+impl EvaluationRow {
+    pub fn new(id: &Id, evaluation: Evaluation) -> Self {
+        Self {
+            id: id.to_string(),
+            precision: format!("{:.2}", evaluation.precision),
+            recall: format!("{:.2}", evaluation.recall),
+            f1: format!("{:.2}", evaluation.f1),
+            false_positives: evaluation.false_positives.join(", "),
+            false_negatives: evaluation.false_negatives.join(", "),
+        }
+    }
+}
+
+#[derive(Tabled)]
+pub struct CountRow {
+    pub id: String,
+    pub hits: String,
+}
+
+impl CountRow {
+    pub fn new(id: &Id, count: &Result<u64, Q3Error>) -> Self {
+        Self {
+            id: id.to_string(),
+            hits: match count {
+                Ok(count) => count.to_string(),
+                Err(err) => err.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Tabled)]
+pub struct ContributionRow {
+    pub item: String,
+    /// Hits of the query when the list only holds the item
+    pub hits: u64,
+    /// Hits lost when the item is removed from the list
+    pub unique: u64,
+}
+
+#[derive(Tabled)]
+pub struct ComponentRow {
+    pub id: String,
+    pub kind: String,
+    pub status: String,
+    pub dependencies: String,
+    pub owner: String,
+    pub tags: String,
+    pub description: String,
+}
+
+impl From<&Q3Components> for ComponentRow {
+    fn from(component: &Q3Components) -> Self {
+        Self {
+            id: component.get_id().to_string(),
+            kind: component.kind().to_string(),
+            status: component.metadata().status.to_string(),
+            dependencies: component
+                .dependencies()
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            owner: component.metadata().owner.clone().unwrap_or_default(),
+            tags: component.metadata().tags.join(", "),
+            description: component.metadata().description.clone().unwrap_or_default(),
+        }
+    }
+}
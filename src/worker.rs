@@ -0,0 +1,397 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use pyo3::exceptions::PyMemoryError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+use crate::sandbox::{self, Sandbox};
+use crate::trace::{Stages, TRACER};
+use crate::{rhai_script, Id, Limits, Q3Error, ScriptLang};
+
+/// Command started to run a script in its own process, which answers with [`serve`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Workers {
+    command: WorkerCommand,
+    /// Whether every script runs in a worker, rather than only the scripts with limits
+    all_scripts: bool,
+}
+
+static WORKERS: RwLock<Option<Workers>> = RwLock::new(None);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    script: String,
+    lang: ScriptLang,
+    /// Items of a list, `None` for generators
+    value: Option<Vec<String>>,
+    /// Bytes of address space the worker may use
+    memory_limit_bytes: Option<u64>,
+    sandbox: Option<Sandbox>,
+    /// Whether to record the value after each statement, see [`crate::trace`]
+    #[serde(default)]
+    trace: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Response {
+    Value {
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        stages: Stages,
+        value: String,
+    },
+    Failed(String),
+    RhaiFailed(String),
+    Unassigned,
+    OutOfMemory,
+}
+
+fn set_workers(workers: Workers) {
+    *WORKERS.write().unwrap_or_else(|err| err.into_inner()) = Some(workers);
+}
+
+/// Runs the scripts with [`Limits`] in worker processes started with `command` from now on.
+/// Without it, scripts with limits fail to run.
+pub fn register(command: WorkerCommand) {
+    set_workers(Workers {
+        command,
+        all_scripts: false,
+    });
+}
+
+/// Runs the scripts of lists and generators in worker processes started with `command` from
+/// now on, so scripts running in parallel don't wait for each other's Python lock
+pub fn enable(command: WorkerCommand) {
+    set_workers(Workers {
+        command,
+        all_scripts: true,
+    });
+}
+
+/// Runs a script in this process, recording its stages if `trace` is set
+fn run_in_process(
+    script: &str,
+    lang: ScriptLang,
+    value: Option<Vec<&str>>,
+    sandbox: Option<&Sandbox>,
+    trace: bool,
+) -> Result<(Stages, String), Q3Error> {
+    // Rhai scripts can't reach anything but their helpers, the sandbox has nothing to restrict.
+    // They aren't run one statement at a time either, they have no stages.
+    if lang == ScriptLang::Rhai {
+        return Ok((Vec::new(), rhai_script::run(script, value)?));
+    }
+
+    Python::with_gil(|py| {
+        let scope = match sandbox {
+            Some(sandbox) => sandbox.globals(py, script)?,
+            None => PyDict::new_bound(py),
+        };
+        scope.set_item("value", value)?;
+
+        let stages: Stages = match trace {
+            true => {
+                let locals = PyDict::new_bound(py);
+                locals.set_item("script", script)?;
+                locals.set_item("scope", &scope)?;
+
+                py.run_bound(TRACER, None, Some(&locals))?;
+
+                locals
+                    .get_item("stages")?
+                    .map(|stages| stages.extract())
+                    .transpose()?
+                    .unwrap_or_default()
+            }
+            false => {
+                py.run_bound(script, Some(&scope), None)?;
+                Vec::new()
+            }
+        };
+
+        let value: String = scope
+            .get_item("value")?
+            .ok_or(Q3Error::PythonScriptVariableNotAssigned)?
+            .extract()?;
+
+        Ok((stages, value))
+    })
+}
+
+/// Whether a worker was ended by `SIGABRT`
+#[cfg(unix)]
+fn aborted(status: &ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+
+    const SIGABRT: i32 = 6;
+
+    status.signal() == Some(SIGABRT)
+}
+
+#[cfg(not(unix))]
+fn aborted(_status: &ExitStatus) -> bool {
+    false
+}
+
+fn run_in_worker(
+    command: &WorkerCommand,
+    id: &Id,
+    request: &Request,
+    limits: &Limits,
+) -> Result<(Stages, String), Q3Error> {
+    let mut child = Command::new(&command.program)
+        .args(&command.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| Q3Error::ScriptWorkerFailed("no standard input".into()))?
+        .write_all(
+            &serde_json::to_vec(request)
+                .map_err(|err| Q3Error::ScriptWorkerFailed(err.to_string()))?,
+        )?;
+
+    // Read aside, so a large output doesn't block the worker while its time runs
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| Q3Error::ScriptWorkerFailed("no standard output".into()))?;
+    let reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    // A deadline too far away to be represented is never reached
+    let deadline = limits
+        .timeout(id)?
+        .and_then(|timeout| Instant::now().checked_add(timeout));
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            child.kill()?;
+            child.wait()?;
+
+            return Err(Q3Error::ScriptTimedOut(
+                id.clone(),
+                limits.timeout.unwrap_or_default(),
+            ));
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    let output = reader
+        .join()
+        .map_err(|_| Q3Error::ScriptWorkerFailed("failed to read the output".into()))??;
+
+    if !status.success() {
+        return match limits.memory_limit {
+            // Allocations failing outside of Python abort the worker
+            Some(memory_limit) if aborted(&status) => {
+                Err(Q3Error::ScriptMemoryLimitExceeded(id.clone(), memory_limit))
+            }
+            _ => Err(Q3Error::ScriptWorkerFailed(status.to_string())),
+        };
+    }
+
+    match serde_json::from_slice(&output)
+        .map_err(|err| Q3Error::ScriptWorkerFailed(err.to_string()))?
+    {
+        Response::Value { stages, value } => Ok((stages, value)),
+        Response::Failed(err) => Err(Q3Error::WorkerScriptFailed(err)),
+        Response::RhaiFailed(err) => Err(Q3Error::RhaiScriptFailed(err)),
+        Response::Unassigned => Err(Q3Error::PythonScriptVariableNotAssigned),
+        Response::OutOfMemory => Err(Q3Error::ScriptMemoryLimitExceeded(
+            id.clone(),
+            limits.memory_limit.unwrap_or_default(),
+        )),
+    }
+}
+
+fn run(
+    id: &Id,
+    script: &str,
+    lang: ScriptLang,
+    value: Option<Vec<&str>>,
+    limits: &Limits,
+    trace: bool,
+) -> Result<(Stages, String), Q3Error> {
+    let workers = WORKERS
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    let sandbox = sandbox::current();
+
+    match workers {
+        Some(workers) if workers.all_scripts || !limits.is_empty() => {
+            let request = Request {
+                script: script.into(),
+                lang,
+                value: value.map(|items| items.into_iter().map(String::from).collect()),
+                memory_limit_bytes: limits.memory_limit_bytes(id)?,
+                sandbox,
+                trace,
+            };
+
+            run_in_worker(&workers.command, id, &request, limits)
+        }
+        None if !limits.is_empty() => Err(Q3Error::ScriptWorkerFailed(format!(
+            "{id} has limits, which need a worker command to be registered"
+        ))),
+        _ => run_in_process(script, lang, value, sandbox.as_ref(), trace),
+    }
+}
+
+/// Runs the script of the component `id` with `value` bound to the items of a list, or to
+/// `None` for a generator, and returns what the script assigned to `value`
+pub(crate) fn run_script(
+    id: &Id,
+    script: &str,
+    lang: ScriptLang,
+    value: Option<Vec<&str>>,
+    limits: &Limits,
+) -> Result<String, Q3Error> {
+    Ok(run(id, script, lang, value, limits, false)?.1)
+}
+
+/// Runs a script like [`run_script`], along with the value it produced after each of its top
+/// level statements
+pub(crate) fn trace_script(
+    id: &Id,
+    script: &str,
+    lang: ScriptLang,
+    value: Option<Vec<&str>>,
+    limits: &Limits,
+) -> Result<(Stages, String), Q3Error> {
+    run(id, script, lang, value, limits, true)
+}
+
+/// Runs the script a worker receives on its standard input and writes the result to its
+/// standard output. Scripts printing to `sys.stdout` write to the standard error instead.
+pub fn serve() -> Result<(), Q3Error> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+
+    let request: Request =
+        serde_json::from_str(&input).map_err(|err| Q3Error::ScriptWorkerFailed(err.to_string()))?;
+
+    Python::with_gil(|py| {
+        py.run_bound("import sys; sys.stdout = sys.stderr", None, None)?;
+
+        if let Some(bytes) = request.memory_limit_bytes {
+            let resource = py.import_bound("resource")?;
+            let limit = resource.getattr("RLIMIT_AS")?;
+
+            resource.call_method1("setrlimit", (limit, (bytes, bytes)))?;
+        }
+
+        Ok::<(), PyErr>(())
+    })?;
+
+    let value = request
+        .value
+        .as_ref()
+        .map(|items| items.iter().map(String::as_str).collect());
+
+    let response = match run_in_process(
+        &request.script,
+        request.lang,
+        value,
+        request.sandbox.as_ref(),
+        request.trace,
+    ) {
+        Ok((stages, value)) => Response::Value { stages, value },
+        Err(Q3Error::PythonScriptVariableNotAssigned) => Response::Unassigned,
+        Err(Q3Error::PythonScriptFailed(err))
+            if Python::with_gil(|py| err.is_instance_of::<PyMemoryError>(py)) =>
+        {
+            Response::OutOfMemory
+        }
+        Err(Q3Error::PythonScriptFailed(err)) => Response::Failed(err.to_string()),
+        Err(Q3Error::RhaiScriptFailed(err)) => Response::RhaiFailed(err),
+        Err(err) => return Err(err),
+    };
+
+    // Flushed here, as a worker started by the Python module exits without flushing Rust's buffer
+    let mut stdout = std::io::stdout();
+    serde_json::to_writer(&mut stdout, &response)
+        .map_err(|err| Q3Error::ScriptWorkerFailed(err.to_string()))?;
+
+    Ok(stdout.flush()?)
+}
+
+#[test]
+fn test_limits_need_a_worker() {
+    let limits = Limits {
+        timeout: Some(1.0),
+        ..Limits::default()
+    };
+
+    assert!(matches!(
+        run_script(
+            &Id("words".into()),
+            "value = ''",
+            ScriptLang::Python,
+            None,
+            &limits
+        ),
+        Err(Q3Error::ScriptWorkerFailed(_))
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_worker_failures() {
+    let run = |exit: &str, limits: &Limits| {
+        let command = WorkerCommand {
+            program: "sh".into(),
+            args: vec!["-c".into(), format!("cat > /dev/null; {exit}")],
+        };
+        let request = Request {
+            script: "value = ''".into(),
+            lang: ScriptLang::Python,
+            value: None,
+            memory_limit_bytes: limits.memory_limit_bytes(&Id("words".into())).unwrap(),
+            sandbox: None,
+            trace: false,
+        };
+
+        run_in_worker(&command, &Id("words".into()), &request, limits)
+    };
+
+    let memory_limit = Limits {
+        memory_limit: Some(64),
+        ..Limits::default()
+    };
+
+    assert!(matches!(
+        run("exit 3", &memory_limit),
+        Err(Q3Error::ScriptWorkerFailed(_))
+    ));
+    assert!(matches!(
+        run("kill -ABRT $$", &memory_limit),
+        Err(Q3Error::ScriptMemoryLimitExceeded(_, 64))
+    ));
+    assert!(matches!(
+        run("kill -ABRT $$", &Limits::default()),
+        Err(Q3Error::ScriptWorkerFailed(_))
+    ));
+}
@@ -0,0 +1,103 @@
+//! Sandboxed scripts run in worker processes, which only the q3 binary can start
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Writes a q3 file with a list running `script`, and runs q3 on it with `args` followed by the
+/// path of the file and `extra`
+fn q3(name: &str, script: &str, args: &[&str], extra: &[&str]) -> Output {
+    let dir = std::env::temp_dir().join(format!("q3-sandbox-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let path: PathBuf = dir.join("query.toml");
+    std::fs::write(
+        &path,
+        format!(
+            "[list.words]\nvalue = \"lorem ipsum\"\nseparator = \" \"\nscript = '''\n{script}\n'''\n\n\
+             [query.title]\nvalue = \"title:(#{{words}})\"\n"
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_q3"))
+        .current_dir(&dir)
+        .args(["--no-cache", "--sandbox"])
+        .args(args)
+        .arg(&path)
+        .args(extra)
+        .output()
+        .unwrap();
+
+    std::fs::remove_dir_all(dir).unwrap();
+
+    output
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+#[test]
+fn test_sandboxed_workers() {
+    let output = q3(
+        "helpers",
+        "from q3 import *\nvalue = join_or(quote(value))",
+        &["get"],
+        &["title"],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), r#"title:("lorem" OR "ipsum")"#);
+
+    let output = q3(
+        "import",
+        "import os\nvalue = os.getcwd()",
+        &["get"],
+        &["title"],
+    );
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("import of `os` is not allowed in the sandbox"));
+
+    let output = q3(
+        "allowed",
+        "import json\nvalue = json.dumps(value)",
+        &["--allow-module", "json", "get"],
+        &["title"],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), r#"title:(["lorem", "ipsum"])"#);
+
+    let output = q3(
+        "private",
+        "from json import _default_decoder\nvalue = ''",
+        &["--allow-module", "json", "get"],
+        &["title"],
+    );
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("`_default_decoder` is not allowed in the sandbox"));
+}
+
+#[test]
+fn test_sandboxed_explain_and_parallel_build() {
+    let script = "value = [item for item in ().__class__.__base__.__subclasses__()]";
+
+    let output = q3("explain", script, &["explain"], &["title"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("`__subclasses__` is not allowed in the sandbox"));
+
+    let output = q3("jobs", script, &["build", "--jobs", "2"], &[]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("`__subclasses__` is not allowed in the sandbox"));
+
+    let output = q3(
+        "traced",
+        "value = ' OR '.join(value)",
+        &["explain"],
+        &["title"],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("value = ' OR '.join(value) -> lorem OR ipsum"));
+}